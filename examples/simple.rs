@@ -2,7 +2,7 @@ use std::time::Duration;
 use throttle_timer::ThrottleTimer;
 
 fn main() {
-    let mut break_timer = ThrottleTimer::new(Duration::from_secs(10_u64), &"Break");
+    let mut break_timer = ThrottleTimer::new(Duration::from_secs(10_u64), "Break");
     let mut val = 0_u8;
     // timers always run when no previous runs
     assert!(break_timer.run(&mut || val += 1));
@@ -0,0 +1,92 @@
+use crate::clock::Clock;
+use crate::interval_passed;
+use std::mem::size_of;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Last call time shared by a parent `ThrottleTimer` and its children, created by the first `new_child`
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SharedWindow(Arc<Mutex<Option<Instant>>>);
+
+impl SharedWindow {
+    pub(crate) fn new(maybe_last_called_time: Option<Instant>) -> Self {
+        Self(Arc::new(Mutex::new(maybe_last_called_time)))
+    }
+    pub(crate) fn get(&self) -> Option<Instant> {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+    pub(crate) fn set(&self, maybe_last_called_time: Option<Instant>) {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner) = maybe_last_called_time;
+    }
+    /// Moves the last call time to `now` if `interval` has passed since it. Checks and sets under one lock
+    /// so timers sharing the window on different threads can not both fire in it
+    pub(crate) fn try_consume(&self, now: Instant, interval: Duration) -> bool {
+        let mut maybe_last_called_time = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        let run_flag = interval_passed(*maybe_last_called_time, now, interval);
+        if run_flag {
+            *maybe_last_called_time = Some(now);
+        }
+        run_flag
+    }
+    pub(crate) fn approx_memory_footprint(&self) -> usize {
+        size_of::<(usize, usize, Mutex<Option<Instant>>)>()
+    }
+}
+
+/// Timer sharing its window with a parent `ThrottleTimer` and its siblings, see `ThrottleTimer::new_child`
+#[derive(Debug)]
+pub struct ChildThrottle {
+    event_name: &'static str,
+    max_frequency: Duration,
+    total_calls: usize,
+    shared_window: SharedWindow,
+    clock: Arc<dyn Clock>,
+}
+
+impl ChildThrottle {
+    pub(crate) fn new(
+        event_name: &'static str,
+        max_frequency: Duration,
+        shared_window: SharedWindow,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        Self {
+            event_name,
+            max_frequency,
+            total_calls: 0,
+            shared_window,
+            clock,
+        }
+    }
+    pub const fn event_name(&self) -> &str {
+        self.event_name
+    }
+    /// Fires of this child only, fires of the parent and siblings are not counted
+    pub const fn total_calls(&self) -> &usize {
+        &self.total_calls
+    }
+    pub const fn max_frequency(&self) -> &Duration {
+        &self.max_frequency
+    }
+
+    /// True if neither the parent nor any child fired within the last `max_frequency`
+    pub fn can_run(&self) -> bool {
+        interval_passed(
+            self.shared_window.get(),
+            self.clock.now(),
+            self.max_frequency,
+        )
+    }
+
+    /// Runs `success` and consumes the shared window if it is open
+    pub fn run(&mut self, success: &mut dyn FnMut()) -> bool {
+        let run_flag = self
+            .shared_window
+            .try_consume(self.clock.now(), self.max_frequency);
+        if run_flag {
+            self.total_calls = self.total_calls.saturating_add(1);
+            success();
+        }
+        run_flag
+    }
+}
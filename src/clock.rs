@@ -0,0 +1,208 @@
+//! Time sources for throttles
+//!
+//! Timers read the time through a `Clock`. `SystemClock` is used by default,
+//! `MockClock` can be injected to make tests deterministic.
+
+use crate::error::Error;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Source of the current time
+pub trait Clock: fmt::Debug + Send + Sync {
+    /// Monotonic time used for throttle windows
+    fn now(&self) -> Instant;
+    /// Wall clock time used for stats and epoch aligned modes
+    fn system_now(&self) -> SystemTime;
+    /// Blocks for `duration`, used by `ThrottleTimer::run_wait`
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration)
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for Arc<C> {
+    fn now(&self) -> Instant {
+        (**self).now()
+    }
+    fn system_now(&self) -> SystemTime {
+        (**self).system_now()
+    }
+    fn sleep(&self, duration: Duration) {
+        (**self).sleep(duration)
+    }
+}
+
+/// Reads the real system time
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+    fn system_now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct MockTime {
+    instant: Instant,
+    system: SystemTime,
+}
+
+/// Clock that only moves when told to
+///
+/// Clones share the same time, so a test can keep a handle while a timer owns another.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use throttle_timer::{MockClock, ThrottleTimer};
+///
+/// let clock = MockClock::new();
+/// let mut timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Break").with_clock(clock.clone());
+///
+/// assert!(timer.run(&mut || {}));
+/// assert!(!timer.run(&mut || {}));
+/// clock.advance(Duration::from_secs(1_u64));
+/// assert!(timer.run(&mut || {}));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    time: Arc<Mutex<MockTime>>,
+}
+
+impl MockClock {
+    /// Starts at the current real time
+    pub fn new() -> Self {
+        Self::at(SystemTime::now())
+    }
+
+    /// Starts with the wall clock reading `system`
+    pub fn at(system: SystemTime) -> Self {
+        Self {
+            time: Arc::new(Mutex::new(MockTime {
+                instant: Instant::now(),
+                system,
+            })),
+        }
+    }
+
+    /// Moves both the monotonic and wall clock forward
+    ///
+    /// # Panics
+    /// If the time would overflow, see `try_advance`
+    pub fn advance(&self, duration: Duration) {
+        if let Err(e) = self.try_advance(duration) {
+            panic!("{}", e);
+        }
+    }
+
+    /// Same as `advance` but leaves the clock unchanged and returns `Error::TimeOverflow` if the time would overflow
+    pub fn try_advance(&self, duration: Duration) -> Result<(), Error> {
+        let mut time = self.time();
+        let instant = time.instant.checked_add(duration);
+        let system = time.system.checked_add(duration);
+        match (instant, system) {
+            (Some(instant), Some(system)) => {
+                *time = MockTime { instant, system };
+                Ok(())
+            }
+            _ => Err(Error::TimeOverflow),
+        }
+    }
+
+    /// Sets the wall clock only, leaving monotonic time untouched. Useful for simulating clock skew
+    pub fn set_system_time(&self, system: SystemTime) {
+        self.time().system = system;
+    }
+
+    fn time(&self) -> MutexGuard<'_, MockTime> {
+        self.time.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.time().instant
+    }
+    fn system_now(&self) -> SystemTime {
+        self.time().system
+    }
+    /// Advances the clock instead of blocking, leaving it unchanged if the time would overflow
+    fn sleep(&self, duration: Duration) {
+        let _ = self.try_advance(duration);
+    }
+}
+
+/// Clock that runs `factor` times faster than the clock it wraps, starting from the time it was created.
+///
+/// Useful for integration tests, a factor of 1000 makes a one second throttle behave like one millisecond.
+/// Sleeping for a scaled duration sleeps the wrapped clock for `duration / factor`
+#[derive(Debug, Clone)]
+pub struct ScaledClock {
+    inner: Arc<dyn Clock>,
+    factor: f64,
+    anchor_instant: Instant,
+    anchor_system: SystemTime,
+}
+
+impl ScaledClock {
+    /// Anchors at `inner`'s current time so readings carry on from it without a jump.
+    /// Factors that are not finite and positive are treated as 1
+    pub fn new<C: Clock + 'static>(inner: C, factor: f64) -> Self {
+        let factor = if factor.is_finite() && factor > 0.0 {
+            factor
+        } else {
+            1.0
+        };
+        Self {
+            anchor_instant: inner.now(),
+            anchor_system: inner.system_now(),
+            inner: Arc::new(inner),
+            factor,
+        }
+    }
+
+    pub const fn factor(&self) -> f64 {
+        self.factor
+    }
+
+    fn scaled(&self, elapsed: Duration) -> Duration {
+        Duration::try_from_secs_f64(elapsed.as_secs_f64() * self.factor).unwrap_or(Duration::MAX)
+    }
+
+    fn unscaled(&self, duration: Duration) -> Duration {
+        Duration::try_from_secs_f64(duration.as_secs_f64() / self.factor).unwrap_or(Duration::MAX)
+    }
+}
+
+impl Clock for ScaledClock {
+    fn now(&self) -> Instant {
+        let now = self.inner.now();
+        let elapsed = now.saturating_duration_since(self.anchor_instant);
+        self.anchor_instant
+            .checked_add(self.scaled(elapsed))
+            .unwrap_or(now)
+    }
+    fn system_now(&self) -> SystemTime {
+        let now = self.inner.system_now();
+        let elapsed = now
+            .duration_since(self.anchor_system)
+            .unwrap_or(Duration::from_secs(0));
+        self.anchor_system
+            .checked_add(self.scaled(elapsed))
+            .unwrap_or(now)
+    }
+    fn sleep(&self, duration: Duration) {
+        self.inner.sleep(self.unscaled(duration))
+    }
+}
@@ -0,0 +1,28 @@
+use std::error;
+use std::fmt;
+use std::time::Duration;
+
+/// Errors returned by the fallible throttle methods
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// The wall clock reads earlier than a previously recorded time, by the contained duration
+    ClockWentBackwards(Duration),
+    /// `Histogram::merge` was given histograms with different bucket bounds
+    HistogramMismatch,
+    /// A time or duration is too far in the future to be represented
+    TimeOverflow,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::ClockWentBackwards(duration) => {
+                write!(f, "clock went backwards by {:?}", duration)
+            }
+            Error::HistogramMismatch => write!(f, "histogram bucket bounds do not match"),
+            Error::TimeOverflow => write!(f, "time is too far in the future to be represented"),
+        }
+    }
+}
+
+impl error::Error for Error {}
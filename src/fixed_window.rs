@@ -0,0 +1,149 @@
+use crate::clock::{Clock, SystemClock};
+use crate::error::Error;
+use crate::{epoch_bucket, until_next_epoch_boundary};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+/// Admits up to `limit` runs per fixed `window`, the classic API rate limit.
+///
+/// Windows are aligned to the UNIX epoch, so a one minute window resets at the top of every wall clock minute.
+/// A zero `window` or a wall clock before the UNIX epoch starts a new window on every call.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use throttle_timer::FixedWindowThrottle;
+///
+/// let mut api_limit = FixedWindowThrottle::new(2, Duration::from_secs(3_600_u64));
+/// assert!(api_limit.run(&mut || {}));
+/// assert!(api_limit.run(&mut || {}));
+/// assert!(!api_limit.run(&mut || {}));
+/// assert_eq!(api_limit.remaining(), 0);
+/// ```
+#[derive(Debug)]
+pub struct FixedWindowThrottle {
+    limit: usize,
+    window: Duration,
+    count: usize,
+    maybe_bucket: Option<u128>,
+    clock: Arc<dyn Clock>,
+}
+
+impl FixedWindowThrottle {
+    pub fn new(limit: usize, window: Duration) -> Self {
+        Self {
+            limit,
+            window,
+            count: 0,
+            maybe_bucket: None,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Reads time from `clock` instead of the system clock
+    pub fn with_clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+    pub const fn limit(&self) -> usize {
+        self.limit
+    }
+    pub const fn window(&self) -> &Duration {
+        &self.window
+    }
+
+    /// Runs left in the current window
+    pub fn remaining(&self) -> usize {
+        self.limit.saturating_sub(self.count_in_window())
+    }
+
+    /// Wall clock time the current window ends and the count resets
+    ///
+    /// # Panics
+    /// If the time would overflow `SystemTime`, see `try_resets_at`
+    pub fn resets_at(&self) -> SystemTime {
+        match self.try_resets_at() {
+            Ok(resets_at) => resets_at,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Same as `resets_at` but returns `Error::TimeOverflow` if the time would overflow `SystemTime`
+    pub fn try_resets_at(&self) -> Result<SystemTime, Error> {
+        let now = self.clock.system_now();
+        now.checked_add(until_next_epoch_boundary(now, self.window).unwrap_or_default())
+            .ok_or(Error::TimeOverflow)
+    }
+
+    pub fn can_run(&self) -> bool {
+        self.remaining() > 0
+    }
+
+    /// Runs `success` if the current window has runs remaining
+    pub fn run(&mut self, success: &mut dyn FnMut()) -> bool {
+        let bucket = self.current_bucket();
+        if bucket.is_none() || bucket != self.maybe_bucket {
+            self.maybe_bucket = bucket;
+            self.count = 0;
+        }
+        let run_flag = self.count < self.limit;
+        if run_flag {
+            self.count = self.count.saturating_add(1);
+            success();
+        }
+        run_flag
+    }
+
+    fn current_bucket(&self) -> Option<u128> {
+        epoch_bucket(self.clock.system_now(), self.window)
+    }
+
+    fn count_in_window(&self) -> usize {
+        match self.current_bucket() {
+            Some(bucket) if self.maybe_bucket == Some(bucket) => self.count,
+            _ => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::FixedWindowThrottle;
+    use crate::{Error, MockClock};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn test_fixed_window() {
+        let clock = MockClock::at(UNIX_EPOCH + Duration::from_secs(90_u64));
+        let mut api_limit =
+            FixedWindowThrottle::new(3, Duration::from_secs(60_u64)).with_clock(clock.clone());
+
+        assert_eq!(api_limit.remaining(), 3);
+        for _ in 0..3 {
+            assert!(api_limit.run(&mut || {}));
+        }
+        assert_eq!(api_limit.remaining(), 0);
+        assert!(!api_limit.run(&mut || {}));
+        assert_eq!(
+            api_limit.resets_at(),
+            UNIX_EPOCH + Duration::from_secs(120_u64)
+        );
+
+        clock.advance(Duration::from_secs(29_u64));
+        assert!(!api_limit.run(&mut || {}));
+
+        // rolls over at the minute boundary, not 60s after the first run
+        clock.advance(Duration::from_secs(1_u64));
+        assert_eq!(api_limit.remaining(), 3);
+        assert!(api_limit.run(&mut || {}));
+        assert_eq!(api_limit.remaining(), 2);
+    }
+
+    #[test]
+    fn test_try_resets_at_overflow() {
+        let api_limit = FixedWindowThrottle::new(3, Duration::MAX)
+            .with_clock(MockClock::at(UNIX_EPOCH + Duration::from_secs(90_u64)));
+        assert_eq!(api_limit.try_resets_at(), Err(Error::TimeOverflow));
+        assert_eq!(api_limit.remaining(), 3);
+    }
+}
@@ -0,0 +1,130 @@
+use crate::error::Error;
+use std::time::Duration;
+
+/// Counts durations into buckets with fixed upper bounds.
+///
+/// A duration goes into the first bucket whose bound it does not exceed, durations above every bound
+/// go into a final overflow bucket, so there is always one more count than there are bounds.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use throttle_timer::Histogram;
+///
+/// let mut latency = Histogram::new(vec![Duration::from_millis(10_u64), Duration::from_millis(100_u64)]);
+/// latency.record(Duration::from_millis(5_u64));
+/// latency.record(Duration::from_millis(50_u64));
+/// latency.record(Duration::from_secs(1_u64));
+/// assert_eq!(latency.counts(), &[1, 1, 1]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    bounds: Vec<Duration>,
+    counts: Vec<usize>,
+}
+
+impl Histogram {
+    /// Bounds are sorted and duplicates dropped
+    pub fn new(mut bounds: Vec<Duration>) -> Self {
+        bounds.sort();
+        bounds.dedup();
+        let counts = vec![0; bounds.len() + 1];
+        Self { bounds, counts }
+    }
+
+    /// Sums the bucket counts of `histograms` into a new histogram, e.g. to combine per thread timers.
+    /// Returns `Error::HistogramMismatch` unless every histogram has the same bounds.
+    /// An empty slice gives an empty histogram with no bounds
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::Histogram;
+    ///
+    /// let bounds = vec![Duration::from_millis(10_u64)];
+    /// let mut first_shard = Histogram::new(bounds.clone());
+    /// first_shard.record(Duration::from_millis(1_u64));
+    /// let mut second_shard = Histogram::new(bounds);
+    /// second_shard.record(Duration::from_millis(1_u64));
+    ///
+    /// let merged = Histogram::merge(&[&first_shard, &second_shard]).unwrap();
+    /// assert_eq!(merged.counts(), &[2, 0]);
+    /// ```
+    pub fn merge(histograms: &[&Histogram]) -> Result<Histogram, Error> {
+        let mut merged = match histograms.first() {
+            None => return Ok(Histogram::new(Vec::new())),
+            Some(&first) => first.clone(),
+        };
+        for histogram in &histograms[1..] {
+            if histogram.bounds != merged.bounds {
+                return Err(Error::HistogramMismatch);
+            }
+            for (count, other_count) in merged.counts.iter_mut().zip(&histogram.counts) {
+                *count = count.saturating_add(*other_count);
+            }
+        }
+        Ok(merged)
+    }
+
+    pub fn record(&mut self, duration: Duration) {
+        let bucket = self.bounds.partition_point(|&bound| bound < duration);
+        self.counts[bucket] = self.counts[bucket].saturating_add(1);
+    }
+    pub fn bounds(&self) -> &[Duration] {
+        &self.bounds
+    }
+    /// Count per bucket, the last being the overflow bucket
+    pub fn counts(&self) -> &[usize] {
+        &self.counts
+    }
+    /// Durations recorded across all buckets
+    pub fn total(&self) -> usize {
+        self.counts
+            .iter()
+            .fold(0, |total, &count| total.saturating_add(count))
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.counts.iter_mut().for_each(|count| *count = 0);
+    }
+
+    pub(crate) fn approx_memory_footprint(&self) -> usize {
+        self.bounds.capacity() * std::mem::size_of::<Duration>()
+            + self.counts.capacity() * std::mem::size_of::<usize>()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Histogram;
+    use crate::Error;
+    use std::time::Duration;
+
+    #[test]
+    fn test_merge() {
+        let bounds = vec![
+            Duration::from_millis(10_u64),
+            Duration::from_millis(100_u64),
+        ];
+        let mut first_shard = Histogram::new(bounds.clone());
+        let mut second_shard = Histogram::new(bounds.clone());
+        for millis in &[1_u64, 10, 11, 500] {
+            first_shard.record(Duration::from_millis(*millis));
+        }
+        for millis in &[50_u64, 5_000] {
+            second_shard.record(Duration::from_millis(*millis));
+        }
+
+        let merged = Histogram::merge(&[&first_shard, &second_shard]).unwrap();
+        assert_eq!(merged.bounds(), &bounds[..]);
+        assert_eq!(merged.counts(), &[2, 2, 2]);
+        assert_eq!(merged.total(), 6);
+
+        let other_bounds = Histogram::new(vec![Duration::from_millis(10_u64)]);
+        assert_eq!(
+            Histogram::merge(&[&first_shard, &other_bounds]),
+            Err(Error::HistogramMismatch)
+        );
+        assert_eq!(Histogram::merge(&[]).unwrap().total(), 0);
+    }
+}
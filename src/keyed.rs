@@ -0,0 +1,125 @@
+use crate::clock::{Clock, SystemClock};
+use crate::interval_passed;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::mem::size_of;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Throttles each key independently with one shared `max_frequency`, e.g. per user ID.
+///
+/// A last call time is kept for every key seen, call `evict_older_than` periodically to bound memory.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use throttle_timer::KeyedThrottle;
+///
+/// let mut login_limit = KeyedThrottle::new(Duration::from_secs(10_u64));
+/// assert!(login_limit.run("alice", &mut || {}));
+/// assert!(login_limit.run("bob", &mut || {}));
+/// assert!(!login_limit.run("alice", &mut || {}));
+/// ```
+#[derive(Debug)]
+pub struct KeyedThrottle<K: Hash + Eq> {
+    max_frequency: Duration,
+    last_called_times: HashMap<K, Instant>,
+    clock: Arc<dyn Clock>,
+}
+
+impl<K: Hash + Eq> KeyedThrottle<K> {
+    pub fn new(max_frequency: Duration) -> Self {
+        Self {
+            max_frequency,
+            last_called_times: HashMap::new(),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Reads time from `clock` instead of the system clock
+    pub fn with_clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+    pub const fn max_frequency(&self) -> &Duration {
+        &self.max_frequency
+    }
+
+    /// Number of keys with a recorded last call time
+    pub fn len(&self) -> usize {
+        self.last_called_times.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.last_called_times.is_empty()
+    }
+
+    /// Rough estimate in bytes of the memory used, including the capacity of the key map.
+    /// Heap memory owned by the keys themselves and allocator overhead are not counted
+    pub fn approx_memory_footprint(&self) -> usize {
+        size_of::<Self>() + self.last_called_times.capacity() * size_of::<(K, Instant)>()
+    }
+
+    pub fn can_run(&self, key: &K) -> bool {
+        interval_passed(
+            self.last_called_times.get(key).copied(),
+            self.clock.now(),
+            self.max_frequency,
+        )
+    }
+
+    /// Runs `success` if `key` has not fired within `max_frequency`
+    pub fn run(&mut self, key: K, success: &mut dyn FnMut()) -> bool {
+        let run_flag = self.can_run(&key);
+        if run_flag {
+            self.last_called_times.insert(key, self.clock.now());
+            success();
+        }
+        run_flag
+    }
+
+    /// Forgets keys that last fired `age` or longer ago. Keys older than `max_frequency` can fire anyway,
+    /// so evicting them does not change throttling
+    pub fn evict_older_than(&mut self, age: Duration) {
+        let now = self.clock.now();
+        self.last_called_times
+            .retain(|_, last_time| now.duration_since(*last_time) < age);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::KeyedThrottle;
+    use crate::MockClock;
+    use std::time::Duration;
+
+    #[test]
+    fn test_keyed_run() {
+        let clock = MockClock::new();
+        let mut login_limit =
+            KeyedThrottle::new(Duration::from_secs(10_u64)).with_clock(clock.clone());
+
+        assert!(login_limit.run(1_u32, &mut || {}));
+        clock.advance(Duration::from_secs(5_u64));
+        assert!(login_limit.run(2_u32, &mut || {}));
+        assert!(!login_limit.run(1_u32, &mut || {}));
+        assert!(!login_limit.run(2_u32, &mut || {}));
+
+        clock.advance(Duration::from_secs(5_u64));
+        assert!(login_limit.can_run(&1_u32));
+        assert!(!login_limit.can_run(&2_u32));
+
+        login_limit.evict_older_than(Duration::from_secs(10_u64));
+        assert_eq!(login_limit.len(), 1);
+        assert!(!login_limit.run(2_u32, &mut || {}));
+    }
+
+    #[test]
+    fn test_approx_memory_footprint() {
+        let mut login_limit = KeyedThrottle::new(Duration::from_secs(10_u64));
+        let empty_footprint = login_limit.approx_memory_footprint();
+        for user_id in 0..100_u64 {
+            login_limit.run(user_id, &mut || {});
+        }
+        assert!(login_limit.approx_memory_footprint() > empty_footprint + 100 * 16);
+    }
+}
@@ -1,10 +1,11 @@
 //! Throttle events and record event stats with a simple library
 //!
-//! throttle_timer has no dependencies
+//! throttle_timer has no dependencies by default.
+//! The `tracing` feature emits a `tracing` event carrying `event_name` and `total_calls` on every fire
 //!
 //! `ThrottleTimer` struct is created with a max frequency and label
 //!
-//! ```ThrottleTimer::new(Duration::from_secs(1_u64), &"Once every second");```
+//! ```ThrottleTimer::new(Duration::from_secs(1_u64), "Once every second");```
 //!
 //! Calling ```run()``` will check the last call time. If max frequency time has not passed the fn will return false.
 //! If max_frequency duration has passed since the last call then the fn will return true
@@ -15,7 +16,7 @@
 //! use std::time::Duration;
 //! use throttle_timer::ThrottleTimer;
 //!
-//! let mut break_timer = ThrottleTimer::new(Duration::from_secs(10_u64), &"Break");
+//! let mut break_timer = ThrottleTimer::new(Duration::from_secs(10_u64), "Break");
 //! let mut val = 0_u8;
 //!
 //! // timers always run when no previous runs
@@ -27,26 +28,305 @@
 //! }
 //!
 //! break_timer.print_stats();
-//! // Break called 0/sec, total calls 1, has been running for 10us
+//! // Break called 100000.00/sec, total calls 1, has been running for 10us
 //!
 //! assert_eq!(break_timer.total_calls(), &1);
 //! assert_eq!(val, 1_u8);
 //!
 //!
 //! ```
+//!
+//! # Panics
+//!
+//! The API does not panic, including for extreme inputs such as `Duration::MAX` intervals, delays or costs,
+//! which behave as "never" or are rejected. Counters such as `total_calls` saturate at `usize::MAX`
+//! and printing ignores stdout write errors.
+//! The exceptions are methods returning a time that may not be representable, each with a `try_*`
+//! variant returning `Error::TimeOverflow` instead:
+//!
+//! - `ThrottleTimer::ready_at`, see `ThrottleTimer::try_ready_at`
+//! - `ThrottleTimer::fire_or_deadline`, see `ThrottleTimer::try_fire_or_deadline`
+//! - `FixedWindowThrottle::resets_at`, see `FixedWindowThrottle::try_resets_at`
+//! - `MockClock::advance`, see `MockClock::try_advance`
+//!
+//! `ThrottleTimer::with_min_gap_assertion` panics on purpose in debug builds, and panics in callbacks
+//! and observers are passed through to the caller.
 
-use std::thread;
+mod child;
+mod clock;
+mod error;
+mod fixed_window;
+mod histogram;
+mod keyed;
+mod registry;
+mod token_bucket;
+mod view;
+
+pub use crate::child::ChildThrottle;
+use crate::child::SharedWindow;
+pub use crate::clock::{Clock, MockClock, ScaledClock, SystemClock};
+pub use crate::error::Error;
+pub use crate::fixed_window::FixedWindowThrottle;
+pub use crate::histogram::Histogram;
+pub use crate::keyed::KeyedThrottle;
+pub use crate::registry::ThrottleRegistry;
+pub use crate::token_bucket::TokenBucket;
+pub use crate::view::ReadOnlyThrottleView;
+
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{self, Write};
+use std::mem::size_of;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::Poll;
 use std::time::Duration;
 use std::time::Instant;
 use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 
 #[derive(Debug)]
 pub struct ThrottleTimer {
     maybe_last_called_time: Option<Instant>,
+    /// Last fire of this timer, kept when `open_window` forgets the last call time
+    maybe_last_fired_time: Option<Instant>,
+    maybe_shared_window: Option<SharedWindow>,
+    maybe_last_called_system_time: Option<SystemTime>,
+    maybe_first_called_time: Option<Instant>,
+    /// Fires since `maybe_first_called_time`, which moves forward on an idle reset
+    calls_since_first: usize,
     total_calls: usize,
+    throttled_calls: usize,
+    throttled_streak: usize,
+    success_count: usize,
+    failure_count: usize,
     created_date: SystemTime,
+    created_instant: Instant,
+    startup_delay: Duration,
     max_frequency: Duration,
     event_name: &'static str,
+    prefix: &'static str,
+    quantized: bool,
+    warmup_calls: usize,
+    time_budget: Option<Duration>,
+    budget_spent: VecDeque<(Instant, Duration)>,
+    min_interval: Option<Duration>,
+    max_interval: Option<Duration>,
+    interval_count: usize,
+    interval_total: Duration,
+    idle_reset: Option<u32>,
+    interval_histogram: Option<Histogram>,
+    observers: Observers,
+    maybe_suspended_until: Option<Instant>,
+    cooldown: Option<Duration>,
+    cooling_down: bool,
+    history: VecDeque<Instant>,
+    history_capacity: usize,
+    color: bool,
+    reset_on_frequency_change: bool,
+    timing: bool,
+    min_gap_assertion: bool,
+    callback_time: Duration,
+    rng_state: u64,
+    clock_resolution: Option<Duration>,
+    clock_cache: ClockCache,
+    clock: Arc<dyn Clock>,
+}
+
+/// Snapshot of a timer's counters, see `ThrottleTimer::stats`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    pub event_name: &'static str,
+    pub total_calls: usize,
+    pub throttled_calls: usize,
+    pub total_attempts: usize,
+    /// Time since the timer was created, zero if the wall clock went backwards
+    pub uptime: Duration,
+}
+
+impl Stats {
+    /// Counts and rate between an earlier snapshot `prev` and this one
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let mut break_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Break");
+    /// let prev = break_timer.stats();
+    /// break_timer.run(&mut || {});
+    /// assert_eq!(break_timer.stats().since(&prev).total_calls, 1);
+    /// ```
+    pub fn since(&self, prev: &Stats) -> StatsDelta {
+        let total_calls = self.total_calls.saturating_sub(prev.total_calls);
+        let span = self.uptime.checked_sub(prev.uptime).unwrap_or_default();
+        StatsDelta {
+            total_calls,
+            throttled_calls: self.throttled_calls.saturating_sub(prev.throttled_calls),
+            total_attempts: self.total_attempts.saturating_sub(prev.total_attempts),
+            span,
+            calls_per_sec: rate(total_calls, span),
+        }
+    }
+}
+
+/// Difference between two `Stats` snapshots, see `Stats::since`
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatsDelta {
+    pub total_calls: usize,
+    pub throttled_calls: usize,
+    pub total_attempts: usize,
+    /// Time between the two snapshots
+    pub span: Duration,
+    /// Fires per second over `span`, 0.0 if `span` is zero
+    pub calls_per_sec: f64,
+}
+
+/// Snapshot of a timer's mutable state with times stored relative to when it was taken,
+/// see `ThrottleTimer::state` and `ThrottleTimer::reset_to`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThrottleState {
+    /// Time since the last fire, `None` if the window is open
+    pub last_called_ago: Option<Duration>,
+    /// Time since the first fire, `None` if never fired or unknown
+    pub first_called_ago: Option<Duration>,
+    pub total_calls: usize,
+    pub throttled_calls: usize,
+}
+
+/// Summary of the times between fires, see `ThrottleTimer::interval_stats`
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntervalStats {
+    pub min: Duration,
+    pub avg: Duration,
+    pub max: Duration,
+    /// Number of intervals measured, one less than the fires they were measured from
+    pub samples: usize,
+}
+
+/// Most clock reads `with_clock_resolution` will serve from one cached reading
+const MAX_READS_PER_REFRESH: u32 = 16;
+
+/// Most fire times `next_fire_times` allocates room for up front
+const MAX_PREALLOCATED_FIRE_TIMES: usize = 1_024;
+
+/// Most fire times `with_history` allocates room for up front, larger histories grow as they fill
+const MAX_PREALLOCATED_HISTORY: usize = 1_024;
+
+/// `ClockCache::reading_nanos` value when there is no cached reading
+const NO_READING: u64 = u64::MAX;
+
+/// Cached clock reading used by `with_clock_resolution`, atomic so `ThrottleTimer` stays `Sync`
+#[derive(Debug)]
+struct ClockCache {
+    /// Nanoseconds from `created_instant` to the cached reading
+    reading_nanos: AtomicU64,
+    reads_left: AtomicU32,
+    /// Reads served per reading, counting the read that took it
+    reads_per_refresh: AtomicU32,
+}
+
+impl Default for ClockCache {
+    fn default() -> Self {
+        Self {
+            reading_nanos: AtomicU64::new(NO_READING),
+            reads_left: AtomicU32::new(0),
+            reads_per_refresh: AtomicU32::new(1),
+        }
+    }
+}
+
+type Observer = Box<dyn FnMut(&ThrottleTimer) + Send>;
+
+/// Callbacks notified after every fire, see `ThrottleTimer::add_observer`.
+/// Behind a lock so `ThrottleTimer` stays `Sync` without requiring `Sync` observers
+#[derive(Default)]
+struct Observers(Mutex<Vec<Observer>>);
+
+impl Observers {
+    fn get_mut(&mut self) -> &mut Vec<Observer> {
+        self.0.get_mut().unwrap_or_else(PoisonError::into_inner)
+    }
+    fn capacity(&self) -> usize {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .capacity()
+    }
+}
+
+impl fmt::Debug for Observers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let observers = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        write!(f, "Observers({})", observers.len())
+    }
+}
+
+/// True if `interval` has passed since the last call, or there was no last call
+fn interval_passed(
+    maybe_last_called_time: Option<Instant>,
+    now: Instant,
+    interval: Duration,
+) -> bool {
+    match maybe_last_called_time {
+        None => true,
+        Some(last_time) => now.duration_since(last_time) >= interval,
+    }
+}
+
+/// `calls` per second over `span`, 0.0 instead of infinite or NaN when `span` is zero
+fn rate(calls: usize, span: Duration) -> f64 {
+    let rate = calls as f64 / span.as_secs_f64();
+    if rate.is_finite() {
+        rate
+    } else {
+        0.0
+    }
+}
+
+/// Writes a line to stdout, ignoring write errors such as a closed pipe instead of panicking like `println!`
+fn print_line(args: fmt::Arguments<'_>) {
+    let _ = writeln!(io::stdout(), "{}", args);
+}
+
+/// Combines two optional values with `merge`, or returns whichever is set
+fn merge_option<T>(a: Option<T>, b: Option<T>, merge: fn(T, T) -> T) -> Option<T> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(merge(a, b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Non zero seed for the xorshift generator used by `run_sampled`
+fn random_seed() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since_epoch| since_epoch.subsec_nanos())
+        .unwrap_or_default();
+    u64::from(nanos) ^ 0x9E37_79B9_7F4A_7C15
+}
+
+/// Index of the `interval` sized bucket since the UNIX epoch that `time` falls in
+fn epoch_bucket(time: SystemTime, interval: Duration) -> Option<u128> {
+    let interval_nanos = interval.as_nanos();
+    if interval_nanos == 0 {
+        return None;
+    }
+    let since_epoch = time.duration_since(UNIX_EPOCH).ok()?;
+    Some(since_epoch.as_nanos() / interval_nanos)
+}
+
+/// Time from `time` until the start of the next `interval` sized bucket since the UNIX epoch
+fn until_next_epoch_boundary(time: SystemTime, interval: Duration) -> Option<Duration> {
+    let interval_nanos = interval.as_nanos();
+    if interval_nanos == 0 {
+        return None;
+    }
+    let since_epoch = time.duration_since(UNIX_EPOCH).ok()?;
+    let into_bucket = since_epoch.as_nanos() % interval_nanos;
+    let into_bucket = Duration::from_nanos(u64::try_from(into_bucket).ok()?);
+    Some(interval - into_bucket)
 }
 
 ///
@@ -55,7 +335,7 @@ pub struct ThrottleTimer {
 /// use std::time::Duration;
 /// use throttle_timer::ThrottleTimer;
 ///
-/// let mut break_timer: ThrottleTimer = ThrottleTimer::new(Duration::from_secs(1_u64), &"Break");
+/// let mut break_timer: ThrottleTimer = ThrottleTimer::new(Duration::from_secs(1_u64), "Break");
 /// let do_break_flag = break_timer.run(&mut || {});
 ///
 /// // Timers always run when no previous runs
@@ -71,215 +351,3110 @@ impl ThrottleTimer {
     pub fn new(max_frequency: std::time::Duration, event_name: &'static str) -> Self {
         Self {
             maybe_last_called_time: None,
+            maybe_last_fired_time: None,
+            maybe_shared_window: None,
+            maybe_last_called_system_time: None,
+            maybe_first_called_time: None,
+            calls_since_first: 0,
             max_frequency,
             event_name,
+            prefix: "",
             total_calls: 0,
+            throttled_calls: 0,
+            throttled_streak: 0,
+            success_count: 0,
+            failure_count: 0,
             created_date: SystemTime::now(),
+            created_instant: Instant::now(),
+            startup_delay: Duration::from_secs(0),
+            quantized: false,
+            warmup_calls: 0,
+            time_budget: None,
+            budget_spent: VecDeque::new(),
+            min_interval: None,
+            max_interval: None,
+            interval_count: 0,
+            interval_total: Duration::from_secs(0),
+            idle_reset: None,
+            interval_histogram: None,
+            observers: Observers::default(),
+            maybe_suspended_until: None,
+            cooldown: None,
+            cooling_down: false,
+            history: VecDeque::new(),
+            history_capacity: 0,
+            color: false,
+            reset_on_frequency_change: false,
+            timing: false,
+            min_gap_assertion: false,
+            callback_time: Duration::from_secs(0),
+            rng_state: random_seed(),
+            clock_resolution: None,
+            clock_cache: ClockCache::default(),
+            clock: Arc::new(SystemClock),
         }
     }
+
+    /// Resumes a timer from persisted state, with its window measured from `last_called`.
+    /// A `last_called` in the future is clamped to now, so the timer waits one full `max_frequency`
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let mut break_timer =
+    ///     ThrottleTimer::resuming(Duration::from_secs(60_u64), "Break", Instant::now(), 12);
+    /// assert!(!break_timer.run(&mut || {}));
+    /// assert_eq!(break_timer.total_calls(), &12);
+    /// ```
+    pub fn resuming(
+        max_frequency: Duration,
+        event_name: &'static str,
+        last_called: Instant,
+        total_calls: usize,
+    ) -> Self {
+        let mut timer = Self::new(max_frequency, event_name);
+        let now = timer.clock.now();
+        let last_called = last_called.min(now);
+        timer.set_last_called_time(Some(last_called));
+        timer.maybe_last_fired_time = Some(last_called);
+        timer.maybe_last_called_system_time = timer
+            .created_date
+            .checked_sub(now.duration_since(last_called));
+        timer.total_calls = total_calls;
+        timer.calls_since_first = total_calls;
+        timer
+    }
+
+    /// Fires at most once per second
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let timer = ThrottleTimer::per_second("Tick");
+    /// assert_eq!(timer.max_frequency(), &Duration::from_secs(1_u64));
+    /// ```
+    pub fn per_second(event_name: &'static str) -> Self {
+        Self::new(Duration::from_secs(1_u64), event_name)
+    }
+
+    /// Fires at most once per minute
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let timer = ThrottleTimer::per_minute("Report");
+    /// assert_eq!(timer.max_frequency(), &Duration::from_secs(60_u64));
+    /// ```
+    pub fn per_minute(event_name: &'static str) -> Self {
+        Self::new(Duration::from_secs(60_u64), event_name)
+    }
+
+    /// Fires at most once per hour
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let timer = ThrottleTimer::per_hour("Backup");
+    /// assert_eq!(timer.max_frequency(), &Duration::from_secs(3_600_u64));
+    /// ```
+    pub fn per_hour(event_name: &'static str) -> Self {
+        Self::new(Duration::from_secs(3_600_u64), event_name)
+    }
+
+    /// Reads time from `clock` instead of the system clock
+    pub fn with_clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+        self.created_date = clock.system_now();
+        self.created_instant = clock.now();
+        self.clock = Arc::new(clock);
+        self.clock_cache = ClockCache::default();
+        self
+    }
+
+    /// Makes each elapsed nanosecond of the current clock count as `factor` nanoseconds from now on,
+    /// so a factor of 1000 makes a one second throttle behave like one millisecond.
+    /// Intended for tests, see `ScaledClock`
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let mut break_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Break");
+    /// break_timer.scale_time(1_000.0);
+    /// assert!(break_timer.run(&mut || {}));
+    /// std::thread::sleep(Duration::from_millis(2_u64));
+    /// assert!(break_timer.run(&mut || {}));
+    /// ```
+    pub fn scale_time(&mut self, factor: f64) {
+        self.clock = Arc::new(ScaledClock::new(Arc::clone(&self.clock), factor));
+        self.clock_cache = ClockCache::default();
+    }
+
+    /// Throttles every run until `startup_delay` after creation, so a new timer does not fire straight away
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let mut report_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Report")
+    ///     .with_startup_delay(Duration::from_secs(30_u64));
+    /// assert!(!report_timer.run(&mut || {}));
+    /// ```
+    pub fn with_startup_delay(mut self, startup_delay: Duration) -> Self {
+        self.startup_delay = startup_delay;
+        self
+    }
+
+    /// Reuses clock readings to avoid reading the clock on every attempt in hot loops.
+    ///
+    /// Each clock read measures the time between attempts since the previous read, and the reading is
+    /// served to as many attempts as fit in half of `resolution` at that pace, at most 16. While attempts
+    /// keep their pace a reading is at most `resolution` old, and as soon as a read sees them slow down the
+    /// clock is read on every attempt again. Fires always record a fresh reading, so a stale reading can
+    /// only make the timer look not ready yet: fires are never early, only late.
+    ///
+    /// Time can not be checked without reading the clock, so when attempts suddenly slow down the reading
+    /// already handed out is still served, to at most 16 attempts.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let mut hot_timer = ThrottleTimer::new(Duration::from_millis(100_u64), "Hot")
+    ///     .with_clock_resolution(Duration::from_millis(1_u64));
+    /// for _ in 0..1_000 {
+    ///     hot_timer.run(&mut || {});
+    /// }
+    /// ```
+    pub fn with_clock_resolution(mut self, resolution: Duration) -> Self {
+        self.clock_resolution = Some(resolution);
+        self
+    }
+
+    /// Aligns fires to `max_frequency` boundaries since the UNIX epoch instead of the last fire.
+    /// A fire is allowed once per boundary, e.g. once at the top of each second.
+    ///
+    /// Boundaries are computed from `SystemTime`, so they follow wall clock adjustments.
+    /// If the clock jumps backwards the timer will not fire again until the boundary after the last fire
+    /// is reached, and if it jumps forwards the next boundary arrives early.
+    /// Wall clock times before the UNIX epoch fall back to the regular interval check.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let mut log_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Log").with_quantized(true);
+    /// assert!(log_timer.run(&mut || {}));
+    /// ```
+    pub fn with_quantized(mut self, quantized: bool) -> Self {
+        self.quantized = quantized;
+        self
+    }
+
+    /// The first `warmup_calls` runs always fire regardless of timing, then normal throttling applies
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let mut cache_timer = ThrottleTimer::new(Duration::from_secs(60_u64), "Cache").with_warmup(2);
+    /// assert!(cache_timer.run(&mut || {}));
+    /// assert!(cache_timer.run(&mut || {}));
+    /// assert!(!cache_timer.run(&mut || {}));
+    /// ```
+    pub fn with_warmup(mut self, warmup_calls: usize) -> Self {
+        self.warmup_calls = warmup_calls;
+        self
+    }
+
+    /// Treats the timer as fresh when a fire comes more than `idle_multiple` times `max_frequency` after
+    /// the previous one: the idle gap is not recorded and `interval_stats` and `average_interval` start over
+    /// from that fire. Lifetime counters such as `total_calls` are kept.
+    /// Off by default
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let poll_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Poll").with_idle_reset(10);
+    /// ```
+    pub fn with_idle_reset(mut self, idle_multiple: u32) -> Self {
+        self.idle_reset = Some(idle_multiple);
+        self
+    }
+
+    /// Counts the time between fires into a `Histogram` with the given bucket bounds, see `interval_histogram`
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let poll_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Poll")
+    ///     .with_interval_histogram(vec![Duration::from_secs(2_u64), Duration::from_secs(10_u64)]);
+    /// assert_eq!(poll_timer.interval_histogram().unwrap().total(), 0);
+    /// ```
+    pub fn with_interval_histogram(mut self, bounds: Vec<Duration>) -> Self {
+        self.interval_histogram = Some(Histogram::new(bounds));
+        self
+    }
+
+    /// Measures the time spent in success callbacks, see `callback_time`. Off by default to avoid the extra clock reads
+    pub fn with_timing(mut self, timing: bool) -> Self {
+        self.timing = timing;
+        self
+    }
+
+    /// Panics in debug builds if `run` ever fires sooner than the interval after the previous fire.
+    /// Warmup, quantized and explicitly opened windows are allowed to fire early. Meant to catch bugs in tests
+    pub fn with_min_gap_assertion(mut self, min_gap_assertion: bool) -> Self {
+        self.min_gap_assertion = min_gap_assertion;
+        self
+    }
+
+    /// Opens the window whenever `set_max_frequency` changes the frequency
+    pub fn with_reset_on_frequency_change(mut self, reset_on_frequency_change: bool) -> Self {
+        self.reset_on_frequency_change = reset_on_frequency_change;
+        self
+    }
+
+    /// Prepends `prefix` verbatim to the event name in printed messages and `Display`, e.g. `"billing: "`.
+    /// `event_name` is unchanged
+    pub fn with_prefix(mut self, prefix: &'static str) -> Self {
+        self.prefix = prefix;
+        self
+    }
+
+    /// Colours the stats line printed by `print_stats` and `Display` with ANSI escape codes
+    /// by how often the timer is throttled. Off by default so piped output stays clean
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Waits `cooldown` instead of `max_frequency` after a `run_with_cooldown` callback reports failure
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let mut connect_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Connect")
+    ///     .with_cooldown(Duration::from_secs(30_u64));
+    /// assert!(connect_timer.run_with_cooldown(&mut || false));
+    /// assert!(connect_timer.wait_time() > Duration::from_secs(1_u64));
+    /// ```
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = Some(cooldown);
+        self
+    }
+
+    /// Keeps the last `history_capacity` fire times, see `history`. Older entries are overwritten.
+    /// Room for at most 1024 fire times is allocated up front
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let mut break_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Break").with_history(10);
+    /// break_timer.run(&mut || {});
+    /// assert_eq!(break_timer.history().count(), 1);
+    /// ```
+    pub fn with_history(mut self, history_capacity: usize) -> Self {
+        self.history = VecDeque::with_capacity(history_capacity.min(MAX_PREALLOCATED_HISTORY));
+        self.history_capacity = history_capacity;
+        self
+    }
+
+    /// Allows `run_costed` to spend at most `time_budget` of work in any rolling `max_frequency` window
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// // at most 100ms of work per second
+    /// let mut cpu_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Cpu")
+    ///     .with_time_budget(Duration::from_millis(100_u64));
+    /// assert!(cpu_timer.run_costed(Duration::from_millis(60_u64), &mut || {}));
+    /// assert!(!cpu_timer.run_costed(Duration::from_millis(60_u64), &mut || {}));
+    /// ```
+    pub fn with_time_budget(mut self, time_budget: Duration) -> Self {
+        self.time_budget = Some(time_budget);
+        self
+    }
     pub const fn event_name(&self) -> &str {
         self.event_name
     }
+    /// Renames the event while keeping all stats and history.
+    /// A timer in a `ThrottleRegistry` stays registered under its old name
+    pub fn set_event_name(&mut self, event_name: &'static str) {
+        self.event_name = event_name;
+    }
+    pub const fn prefix(&self) -> &str {
+        self.prefix
+    }
     pub const fn total_calls(&self) -> &usize {
         &self.total_calls
     }
+    pub const fn throttled_calls(&self) -> &usize {
+        &self.throttled_calls
+    }
+    /// Total time spent in success callbacks, zero unless `with_timing` is on
+    pub const fn callback_time(&self) -> Duration {
+        self.callback_time
+    }
+    /// Time since the last fire, or creation if never fired, while every attempt since has been throttled.
+    /// Zero if nothing was throttled since the last fire. Useful for escalating when an event is held back for too long
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let mut alert_timer = ThrottleTimer::new(Duration::from_secs(60_u64), "Alert");
+    /// alert_timer.run(&mut || {});
+    /// assert_eq!(alert_timer.throttled_streak_duration(), Duration::from_secs(0_u64));
+    /// alert_timer.run(&mut || {});
+    /// if alert_timer.throttled_streak_duration() > Duration::from_secs(300_u64) {
+    ///     println!("escalating");
+    /// }
+    /// ```
+    pub fn throttled_streak_duration(&self) -> Duration {
+        if self.throttled_streak == 0 {
+            return Duration::from_secs(0);
+        }
+        let streak_start = self.last_called_time().unwrap_or(self.created_instant);
+        self.now().duration_since(streak_start)
+    }
+    /// True if the most recent attempt was throttled and the window is still closed.
+    /// Unlike `is_ready` this stays false for a timer nobody is calling, which suits alerting on active throttling
+    pub fn effective_rate_limited(&self) -> bool {
+        self.throttled_streak > 0 && !self.is_ready()
+    }
+    /// Fires through `run_tallied` whose callback returned `Ok`
+    pub const fn success_count(&self) -> usize {
+        self.success_count
+    }
+    /// Fires through `run_tallied` whose callback returned `Err`
+    pub const fn failure_count(&self) -> usize {
+        self.failure_count
+    }
+    /// Fired plus throttled calls
+    pub const fn total_attempts(&self) -> usize {
+        self.total_calls.saturating_add(self.throttled_calls)
+    }
     pub const fn max_frequency(&self) -> &Duration {
         &self.max_frequency
     }
     pub const fn created_date(&self) -> SystemTime {
         self.created_date
     }
-    pub fn wait_time(&self) -> Duration {
-        match self.maybe_last_called_time {
-            None => Duration::from_secs(0),
-            Some(last_time) => {
-                (self.max_frequency
-                    - Instant::now()
-                        .duration_since(last_time)
-                        .min(self.max_frequency))
-            }
-        }
+    pub const fn quantized(&self) -> bool {
+        self.quantized
+    }
+    pub const fn warmup_calls(&self) -> usize {
+        self.warmup_calls
+    }
+    pub const fn cooldown(&self) -> Option<Duration> {
+        self.cooldown
+    }
+    pub const fn time_budget(&self) -> Option<Duration> {
+        self.time_budget
     }
 
-    /// Prints total calls and calls/sec
-    pub fn print_stats(&self) {
-        match self.created_date.elapsed() {
-            Ok(created_time_elapsed) => {
-                println!(
-                    "{} called {}/sec, total calls {}, has been running for {:?}",
-                    self.event_name,
-                    created_time_elapsed.as_secs() / self.total_calls as u64,
-                    self.total_calls,
-                    created_time_elapsed,
-                );
-            }
-            Err(e) => eprintln!("{:?}", e),
+    /// Changes the minimum time between fires.
+    ///
+    /// The window is checked against the new frequency straight away, so shortening it lets a timer
+    /// fire as soon as the new interval has passed since the last fire.
+    /// With `with_reset_on_frequency_change` the window is also opened so the next run fires regardless
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let mut poll_timer = ThrottleTimer::new(Duration::from_secs(60_u64), "Poll");
+    /// assert!(poll_timer.run(&mut || {}));
+    /// poll_timer.set_max_frequency(Duration::from_secs(0_u64));
+    /// assert!(poll_timer.run(&mut || {}));
+    /// ```
+    pub fn set_max_frequency(&mut self, max_frequency: Duration) {
+        if self.reset_on_frequency_change && max_frequency != self.max_frequency {
+            self.open_window();
         }
+        self.max_frequency = max_frequency;
     }
 
-    /// Calling ```run()``` will check the last call time. If max frequency time has not passed the fn will return false.
-    /// If max_frequency duration has passed since the last call then the fn will return true
-    pub fn can_run(&mut self) -> bool {
-        match self.maybe_last_called_time {
-            None => true,
-            Some(last_time) => Instant::now().duration_since(last_time) >= self.max_frequency,
-        }
+    /// Clamps `max_frequency` into `min..=max` and returns the applied value.
+    /// Useful when frequencies come from untrusted config. If `min` is greater than `max`, `max` wins
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let mut poll_timer = ThrottleTimer::new(Duration::from_secs(0_u64), "Poll");
+    /// let applied = poll_timer.clamp_frequency(Duration::from_secs(1_u64), Duration::from_secs(60_u64));
+    /// assert_eq!(applied, Duration::from_secs(1_u64));
+    /// ```
+    pub fn clamp_frequency(&mut self, min: Duration, max: Duration) -> Duration {
+        self.set_max_frequency(self.max_frequency.max(min).min(max));
+        self.max_frequency
     }
 
-    pub fn run_throttle_cb(&mut self, success: &mut FnMut(), throttled: &mut FnMut()) -> bool {
-        let run_flag: bool = self.can_run();
+    /// Read only view of the timer that can not fire it, for dashboards and other observers
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let break_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Break");
+    /// let view = break_timer.downgrade_to_readonly();
+    /// assert!(view.is_ready());
+    /// ```
+    pub fn downgrade_to_readonly(&self) -> ReadOnlyThrottleView<'_> {
+        ReadOnlyThrottleView::new(self)
+    }
 
-        if run_flag {
-            self.maybe_last_called_time = Some(Instant::now());
-            self.total_calls += 1;
-            success();
-        } else {
-            throttled()
-        }
-        run_flag
+    /// Creates a timer that shares this timer's window. A fire by the parent or any child
+    /// throttles all of them until `max_frequency` has passed, while interval stats and history of the parent
+    /// only count its own fires.
+    /// Children only use the interval check, modes such as `with_quantized` are not inherited.
+    /// The window is shared through a lock created by the first call, timers without children do not use it.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let mut parent = ThrottleTimer::new(Duration::from_secs(1_u64), "Alerts");
+    /// let mut email = parent.new_child("Email");
+    /// let mut sms = parent.new_child("Sms");
+    ///
+    /// assert!(email.run(&mut || {}));
+    /// assert!(!sms.run(&mut || {}));
+    /// ```
+    pub fn new_child(&mut self, event_name: &'static str) -> ChildThrottle {
+        let maybe_last_called_time = self.maybe_last_called_time;
+        let shared_window = self
+            .maybe_shared_window
+            .get_or_insert_with(|| SharedWindow::new(maybe_last_called_time))
+            .clone();
+        ChildThrottle::new(
+            event_name,
+            self.max_frequency,
+            shared_window,
+            Arc::clone(&self.clock),
+        )
     }
 
-    /// Calling ```run()``` will check the last call time. If max frequency time has not passed the fn will return false.
-    /// If max_frequency duration has passed since the last call then the fn will return true
-    pub fn run(&mut self, success: &mut FnMut()) -> bool {
-        self.run_throttle_cb(success, &mut || {})
+    /// Last fire of this timer, or of any child once `new_child` has been called.
+    /// Only for readiness, stats and history measure the timer's own fires from `maybe_last_called_time`
+    fn last_called_time(&self) -> Option<Instant> {
+        match &self.maybe_shared_window {
+            Some(shared_window) => shared_window.get(),
+            None => self.maybe_last_called_time,
+        }
     }
 
-    /// Calling ```run()``` will check the last call time. If max frequency time has not passed the fn will return false.
-    /// If max_frequency duration has passed since the last call then the fn will return true
-    pub fn run_wait(&mut self, success: &mut FnMut()) {
-        thread::sleep(self.wait_time());
-        self.run_throttle_cb(success, &mut || {});
+    fn set_last_called_time(&mut self, maybe_last_called_time: Option<Instant>) {
+        self.maybe_last_called_time = maybe_last_called_time;
+        if let Some(shared_window) = &self.maybe_shared_window {
+            shared_window.set(maybe_last_called_time);
+        }
+    }
+    /// Shortest time between two fires, `None` before the second fire
+    pub const fn min_interval(&self) -> Option<Duration> {
+        self.min_interval
+    }
+    /// Longest time between two fires, `None` before the second fire
+    pub const fn max_interval(&self) -> Option<Duration> {
+        self.max_interval
+    }
+    /// Snapshot of the timer's counters
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let mut break_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Break");
+    /// break_timer.run(&mut || {});
+    /// break_timer.run(&mut || {});
+    ///
+    /// let stats = break_timer.stats();
+    /// assert_eq!(stats.total_calls, 1);
+    /// assert_eq!(stats.throttled_calls, 1);
+    /// assert_eq!(stats.total_attempts, 2);
+    /// ```
+    pub fn stats(&self) -> Stats {
+        Stats {
+            event_name: self.event_name,
+            total_calls: self.total_calls,
+            throttled_calls: self.throttled_calls,
+            total_attempts: self.total_attempts(),
+            uptime: self.uptime().unwrap_or_default(),
+        }
     }
 
-    // Same as run but will print a message if throttled
-    pub fn run_with_msg(&mut self, success: &mut FnMut()) -> bool {
-        let did_run = self.run(success);
-        if !did_run {
-            println!(
-                "{} throttled, last time {:?}",
-                self.event_name(),
-                Instant::now().duration_since(self.maybe_last_called_time.unwrap())
-            );
+    /// Snapshot of the timer's state that can be restored with `reset_to`
+    pub fn state(&self) -> ThrottleState {
+        let now = self.now();
+        ThrottleState {
+            last_called_ago: self
+                .last_called_time()
+                .map(|last_time| now.duration_since(last_time)),
+            first_called_ago: self
+                .maybe_first_called_time
+                .map(|first_time| now.duration_since(first_time)),
+            total_calls: self.total_calls,
+            throttled_calls: self.throttled_calls,
         }
-        did_run
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::ThrottleTimer;
-    use std::{thread, time::Duration};
+    /// Overwrites the timer's state, e.g. to set up edge cases in tests without sleeping.
+    /// Times further back than the clock can represent are treated as unknown.
+    ///
+    /// State not carried by `ThrottleState` is cleared: interval stats and histogram, fire history,
+    /// success and failure tallies, callback time, spent time budget, any suspension and a pending cooldown.
+    /// Configuration set by the `with_*` builders, observers and the clock are kept
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::{ThrottleState, ThrottleTimer};
+    ///
+    /// let mut break_timer = ThrottleTimer::new(Duration::from_secs(60_u64), "Break");
+    /// break_timer.reset_to(ThrottleState {
+    ///     last_called_ago: Some(Duration::from_secs(30_u64)),
+    ///     first_called_ago: Some(Duration::from_secs(90_u64)),
+    ///     total_calls: 2,
+    ///     throttled_calls: 5,
+    /// });
+    /// assert!(!break_timer.can_run());
+    /// ```
+    pub fn reset_to(&mut self, state: ThrottleState) {
+        let now = self.now();
+        let system_now = self.clock.system_now();
+        self.set_last_called_time(state.last_called_ago.and_then(|ago| now.checked_sub(ago)));
+        self.maybe_last_fired_time = self.maybe_last_called_time;
+        self.maybe_last_called_system_time = state
+            .last_called_ago
+            .and_then(|ago| system_now.checked_sub(ago));
+        self.maybe_first_called_time = state.first_called_ago.and_then(|ago| now.checked_sub(ago));
+        self.total_calls = state.total_calls;
+        self.calls_since_first = state.total_calls;
+        self.throttled_calls = state.throttled_calls;
+        self.throttled_streak = 0;
+        self.clear_interval_stats();
+        self.history.clear();
+        self.success_count = 0;
+        self.failure_count = 0;
+        self.callback_time = Duration::from_secs(0);
+        self.budget_spent.clear();
+        self.maybe_suspended_until = None;
+        self.cooling_down = false;
+    }
 
-    #[test]
-    fn test_run() {
-        let mut break_timer: ThrottleTimer =
-            ThrottleTimer::new(Duration::from_secs(45_000_u64), &"Break");
-        let run_flag = break_timer.run(&mut || {});
+    /// Min, average and max time between fires, `None` before the second fire
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let break_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Break");
+    /// assert_eq!(break_timer.interval_stats(), None);
+    /// ```
+    pub fn interval_stats(&self) -> Option<IntervalStats> {
+        let samples = self.interval_count;
+        let avg_nanos = self.interval_total.as_nanos() / samples.max(1) as u128;
+        Some(IntervalStats {
+            min: self.min_interval?,
+            avg: Duration::from_nanos(u64::try_from(avg_nanos).unwrap_or(u64::MAX)),
+            max: self.max_interval?,
+            samples,
+        })
+    }
+    /// Time between fires counted into buckets, `None` unless enabled by `with_interval_histogram`
+    pub const fn interval_histogram(&self) -> Option<&Histogram> {
+        self.interval_histogram.as_ref()
+    }
+    /// Average time between fires, from the first to the last fire so time before the first fire is excluded.
+    /// `None` before the second fire, or for timers created by `resuming` as their first fire is unknown
+    pub fn average_interval(&self) -> Option<Duration> {
+        let first_time = self.maybe_first_called_time?;
+        let last_time = self.maybe_last_fired_time?;
+        let intervals = u32::try_from(self.calls_since_first.checked_sub(1)?).ok()?;
+        if intervals == 0 {
+            return None;
+        }
+        Some(last_time.duration_since(first_time) / intervals)
+    }
+    pub fn wait_time(&self) -> Duration {
+        self.blocked_wait_time().max(self.window_wait_time())
+    }
 
-        // timers always run when no previous runs
-        assert!(run_flag);
-        if run_flag {
-            println!("timer do run flag is set to true")
+    fn window_wait_time(&self) -> Duration {
+        if self.in_warmup() {
+            return Duration::from_secs(0);
+        }
+        if let Some(ready) = self.quantized_ready() {
+            if ready {
+                return Duration::from_secs(0);
+            }
+            return until_next_epoch_boundary(self.clock.system_now(), self.max_frequency)
+                .unwrap_or_default();
+        }
+        let interval = self.interval();
+        match self.last_called_time() {
+            None => Duration::from_secs(0),
+            Some(last_time) => interval - self.now().duration_since(last_time).min(interval),
         }
-        break_timer.event_name();
-        break_timer.total_calls();
-        break_timer.max_frequency();
-        break_timer.created_date();
     }
 
-    #[test]
-    fn test_run_with_msg() {
-        let mut break_timer: ThrottleTimer =
-            ThrottleTimer::new(Duration::from_secs(45_000_u64), &"Break");
-        let run_flag = break_timer.run_with_msg(&mut || {});
+    /// Time required since the last fire, the cooldown after a failed `run_with_cooldown` otherwise `max_frequency`
+    fn interval(&self) -> Duration {
+        match self.cooldown {
+            Some(cooldown) if self.cooling_down => cooldown,
+            _ => self.max_frequency,
+        }
+    }
 
-        // timers always run when no previous runs
-        assert!(run_flag);
+    /// Lets the next run fire by forgetting the last call time. Counters and interval stats are kept,
+    /// and the interval to the next fire is not recorded
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let mut break_timer = ThrottleTimer::new(Duration::from_secs(60_u64), "Break");
+    /// assert!(break_timer.run(&mut || {}));
+    /// break_timer.open_window();
+    /// assert!(break_timer.run(&mut || {}));
+    /// ```
+    pub fn open_window(&mut self) {
+        self.set_last_called_time(None);
+        self.maybe_last_called_system_time = None;
     }
 
-    #[test]
-    fn test_call_count() {
-        let mut break_timer: ThrottleTimer =
-            ThrottleTimer::new(Duration::from_nanos(1_u64), &"Break");
+    /// Blocks every fire until `instant`, after which normal throttling resumes.
+    /// Time keeps passing while suspended, so the interval may already be over when the suspension ends
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let mut sync_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Sync");
+    /// sync_timer.suspend_until(Instant::now() + Duration::from_secs(60_u64));
+    /// assert!(!sync_timer.run(&mut || {}));
+    /// ```
+    pub fn suspend_until(&mut self, instant: Instant) {
+        self.maybe_suspended_until = Some(instant);
+    }
 
-        for _ in 0..100 {
-            assert_eq!(break_timer.run(&mut || {}), true);
-            thread::sleep(Duration::from_nanos(100_u64));
+    /// Time left until both the startup delay and any suspension are over
+    fn blocked_wait_time(&self) -> Duration {
+        let now = self.now();
+        let startup_wait_time = self
+            .created_instant
+            .checked_add(self.startup_delay)
+            .map_or(Duration::MAX, |startup_end| {
+                startup_end.saturating_duration_since(now)
+            });
+        self.maybe_suspended_until
+            .map_or_else(Duration::default, |until| {
+                until.saturating_duration_since(now)
+            })
+            .max(startup_wait_time)
+    }
+
+    fn in_warmup(&self) -> bool {
+        self.total_calls < self.warmup_calls
+    }
+
+    /// Instant the timer is next ready to fire, now if it is ready already
+    ///
+    /// # Panics
+    /// If the wait is too long to represent as an `Instant`, e.g. with a `Duration::MAX` startup delay.
+    /// See `try_ready_at`
+    pub fn ready_at(&self) -> Instant {
+        match self.try_ready_at() {
+            Ok(ready_at) => ready_at,
+            Err(e) => panic!("{}", e),
         }
+    }
 
-        // timers always run when no previous runs
-        assert_eq!(break_timer.total_calls, 100);
-        break_timer.print_stats();
+    /// Same as `ready_at` but returns `Error::TimeOverflow` instead of panicking
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::{Error, ThrottleTimer};
+    ///
+    /// let never_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Never")
+    ///     .with_startup_delay(Duration::MAX);
+    /// assert_eq!(never_timer.try_ready_at(), Err(Error::TimeOverflow));
+    /// ```
+    pub fn try_ready_at(&self) -> Result<Instant, Error> {
+        self.now()
+            .checked_add(self.wait_time())
+            .ok_or(Error::TimeOverflow)
     }
 
-    #[test]
-    fn test_can_run() {
-        let mut break_timer: ThrottleTimer =
-            ThrottleTimer::new(Duration::from_secs(1_u64), &"Break");
+    /// Number of fires possible within the next `window` if every fire happens as soon as the timer is ready.
+    /// Zero while the timer stays blocked for all of `window`, otherwise a zero `max_frequency` always fires
+    /// so this returns `usize::MAX`
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let break_timer = ThrottleTimer::new(Duration::from_secs(10_u64), "Break");
+    /// assert_eq!(break_timer.permits_in(Duration::from_secs(60_u64)), 7);
+    /// ```
+    pub fn permits_in(&self, window: Duration) -> usize {
+        let after_ready = match window.checked_sub(self.wait_time()) {
+            None => return 0,
+            Some(after_ready) => after_ready,
+        };
+        let frequency_nanos = self.max_frequency.as_nanos();
+        if frequency_nanos == 0 {
+            return usize::MAX;
+        }
+        usize::try_from(after_ready.as_nanos() / frequency_nanos)
+            .unwrap_or(usize::MAX)
+            .saturating_add(1)
+    }
 
-        assert!(break_timer.run(&mut || {}));
-        for _ in 0..100 {
-            assert!(!break_timer.can_run());
-            assert!(!break_timer.run(&mut || {}));
+    /// The next `n` instants the timer could fire at, if every fire happens as soon as the timer is ready.
+    /// With a zero `max_frequency` the timer can fire again straight away, so only the ready time is returned.
+    /// Stops early if the instants would overflow what `Instant` can represent
+    pub fn next_fire_times(&self, n: usize) -> Vec<Instant> {
+        let n = if self.max_frequency == Duration::from_secs(0) {
+            n.min(1)
+        } else {
+            n
+        };
+        let mut fire_times = Vec::with_capacity(n.min(MAX_PREALLOCATED_FIRE_TIMES));
+        let mut maybe_fire_time = self.try_ready_at().ok();
+        while fire_times.len() < n {
+            let fire_time = match maybe_fire_time {
+                None => break,
+                Some(fire_time) => fire_time,
+            };
+            fire_times.push(fire_time);
+            maybe_fire_time = fire_time.checked_add(self.max_frequency);
         }
+        fire_times
+    }
 
-        // timers always run when no previous runs
-        assert_eq!(break_timer.total_calls, 1);
-        break_timer.print_stats();
+    /// True if the timer will be ready at some point within `window`, always true when ready now
+    pub fn will_fire_within(&self, window: Duration) -> bool {
+        self.wait_time() <= window
     }
 
-    #[test]
-    fn test_print_debug() {
-        println!(
-            "{:?}",
-            ThrottleTimer::new(Duration::from_nanos(1_u64), &"Break")
-        );
+    /// `wait_time` capped at `max_frequency`, for countdown displays that should never show more than one interval
+    pub fn countdown(&self) -> Duration {
+        self.wait_time().min(self.max_frequency)
     }
 
-    #[test]
-    fn test_in_loop() {
-        let mut break_timer = ThrottleTimer::new(Duration::from_secs(10_u64), &"Break");
+    /// Current time, possibly a cached reading when `with_clock_resolution` is set
+    fn now(&self) -> Instant {
+        if self.clock_resolution.is_none() {
+            return self.clock.now();
+        }
+        if let Some(reading) = self.cached_reading() {
+            let reads_left = self.clock_cache.reads_left.load(Ordering::Relaxed);
+            if reads_left > 0 {
+                self.clock_cache
+                    .reads_left
+                    .store(reads_left - 1, Ordering::Relaxed);
+                return reading;
+            }
+        }
+        self.fresh_now()
+    }
 
-        // timers always run when no previous runs
-        assert!(break_timer.run(&mut || {}));
-        for _ in 0..100 {
-            // timer will not run as 10 secs has not passed
-            // do run will return false
-            assert!(!break_timer.run(&mut || {}));
+    /// Reads the clock, bypassing `with_clock_resolution` but refreshing its cached reading
+    fn fresh_now(&self) -> Instant {
+        let now = self.clock.now();
+        if let Some(resolution) = self.clock_resolution {
+            let reads_per_refresh = self.reads_per_refresh(now, resolution);
+            self.clock_cache
+                .reads_per_refresh
+                .store(reads_per_refresh, Ordering::Relaxed);
+            self.cache_reading(now, reads_per_refresh - 1);
         }
-        assert_eq!(break_timer.total_calls(), &1);
+        now
     }
 
-    #[test]
-    fn test_run_wait() {
-        let mut break_timer = ThrottleTimer::new(Duration::from_nanos(10_u64), &"Break");
+    fn cached_reading(&self) -> Option<Instant> {
+        match self.clock_cache.reading_nanos.load(Ordering::Relaxed) {
+            NO_READING => None,
+            reading_nanos => self
+                .created_instant
+                .checked_add(Duration::from_nanos(reading_nanos)),
+        }
+    }
 
-        break_timer.run_wait(&mut || {});
-        break_timer.run_wait(&mut || {});
-        break_timer.run_wait(&mut || {});
-        assert_eq!(break_timer.total_calls(), &3);
+    /// Reads to serve from a reading taken at `now`, as many as fit in half of `resolution` at the pace
+    /// reads arrived since the previous reading. Unchanged if no time passed, as the pace is unknown
+    fn reads_per_refresh(&self, now: Instant, resolution: Duration) -> u32 {
+        let cache = &self.clock_cache;
+        let reads_per_refresh = cache.reads_per_refresh.load(Ordering::Relaxed);
+        let since_reading = match self.cached_reading() {
+            None => return 1,
+            Some(reading) => now.saturating_duration_since(reading),
+        };
+        if since_reading == Duration::from_secs(0) {
+            return reads_per_refresh;
+        }
+        let reads_since_reading =
+            reads_per_refresh.saturating_sub(cache.reads_left.load(Ordering::Relaxed));
+        let reads_in_half_resolution = (resolution / 2).as_nanos()
+            * u128::from(reads_since_reading.max(1))
+            / since_reading.as_nanos();
+        u32::try_from(reads_in_half_resolution)
+            .unwrap_or(MAX_READS_PER_REFRESH)
+            .clamp(1, MAX_READS_PER_REFRESH)
     }
 
-    #[test]
-    fn test_with_delay() {
-        let mut snack_timer: ThrottleTimer =
-            ThrottleTimer::new(Duration::from_secs(1_u64), &"Snack");
-        let run_flag = snack_timer.run(&mut || {});
+    fn cache_reading(&self, reading: Instant, reads_left: u32) {
+        let reading_nanos = reading
+            .checked_duration_since(self.created_instant)
+            .and_then(|since_created| u64::try_from(since_created.as_nanos()).ok())
+            .filter(|&reading_nanos| reading_nanos != NO_READING)
+            .unwrap_or(NO_READING);
+        self.clock_cache
+            .reading_nanos
+            .store(reading_nanos, Ordering::Relaxed);
+        self.clock_cache
+            .reads_left
+            .store(reads_left, Ordering::Relaxed);
+    }
 
-        // timers always run when no previous runs
-        assert!(run_flag);
+    /// Readiness in quantized mode, `None` when the regular interval check applies
+    fn quantized_ready(&self) -> Option<bool> {
+        if !self.quantized {
+            return None;
+        }
+        let last_time = match self.maybe_last_called_system_time {
+            None => return Some(true),
+            Some(last_time) => last_time,
+        };
+        let last_bucket = epoch_bucket(last_time, self.max_frequency)?;
+        let now_bucket = epoch_bucket(self.clock.system_now(), self.max_frequency)?;
+        Some(now_bucket > last_bucket)
+    }
 
-        let run_flag2 = snack_timer.run_with_msg(&mut || {});
+    /// Fires per second since creation. Always finite, 0.0 when no time has passed or the wall clock went backwards
+    pub fn calls_per_sec(&self) -> f64 {
+        rate(self.total_calls, self.uptime().unwrap_or_default())
+    }
 
-        // run flag false as no time has passed
-        assert_eq!(run_flag2, false);
+    /// Prints total calls and calls/sec. Clock errors are ignored, see `try_print_stats`
+    pub fn print_stats(&self) {
+        let _ = self.try_print_stats();
+    }
 
-        thread::sleep(snack_timer.max_frequency);
-        assert!(snack_timer.run(&mut || {}));
-        thread::sleep(Duration::from_millis(100_u64));
-        assert!(!snack_timer.run(&mut || {}));
-        thread::sleep(Duration::from_secs(1_u64));
+    /// Prints total calls and calls/sec, returning an error if the wall clock went backwards since creation
+    pub fn try_print_stats(&self) -> Result<(), Error> {
+        self.uptime()?;
+        print_line(format_args!("{}", self));
+        Ok(())
+    }
+
+    /// ANSI escape code for the stats line colour: green when most attempts fire,
+    /// yellow when throttled often and red when heavily throttled
+    fn stats_color(&self) -> &'static str {
+        let total_attempts = self.total_attempts();
+        if total_attempts == 0 || self.total_calls.saturating_mul(2) >= total_attempts {
+            "\x1b[32m"
+        } else if self.total_calls.saturating_mul(10) >= total_attempts {
+            "\x1b[33m"
+        } else {
+            "\x1b[31m"
+        }
+    }
+
+    fn uptime(&self) -> Result<Duration, Error> {
+        self.clock
+            .system_now()
+            .duration_since(self.created_date)
+            .map_err(|e| Error::ClockWentBackwards(e.duration()))
+    }
+
+    /// Calling ```run()``` will check the last call time. If max frequency time has not passed the fn will return false.
+    /// If max_frequency duration has passed since the last call then the fn will return true
+    pub fn can_run(&mut self) -> bool {
+        self.is_ready()
+    }
+
+    /// Same as `can_run` without needing mutable access
+    pub fn is_ready(&self) -> bool {
+        if self.blocked_wait_time() != Duration::from_secs(0) {
+            return false;
+        }
+        if self.in_warmup() {
+            return true;
+        }
+        if let Some(ready) = self.quantized_ready() {
+            return ready;
+        }
+        interval_passed(self.last_called_time(), self.now(), self.interval())
+    }
+
+    /// Replays `attempts` against a fresh timer without touching any real timer or clock,
+    /// returning whether each attempt would fire. `attempts` should be in chronological order
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let start = Instant::now();
+    /// let attempts = [start, start + Duration::from_millis(500_u64), start + Duration::from_secs(1_u64)];
+    /// assert_eq!(
+    ///     ThrottleTimer::simulate(Duration::from_secs(1_u64), &attempts),
+    ///     vec![true, false, true]
+    /// );
+    /// ```
+    pub fn simulate(max_frequency: Duration, attempts: &[Instant]) -> Vec<bool> {
+        let mut maybe_last_called_time = None;
+        attempts
+            .iter()
+            .map(|&attempt_time| {
+                let run_flag = interval_passed(maybe_last_called_time, attempt_time, max_frequency);
+                if run_flag {
+                    maybe_last_called_time = Some(attempt_time);
+                }
+                run_flag
+            })
+            .collect()
+    }
+
+    pub fn run_throttle_cb(
+        &mut self,
+        success: &mut dyn FnMut(),
+        throttled: &mut dyn FnMut(),
+    ) -> bool {
+        let run_flag: bool = self.can_run() && self.claim_shared_window();
+
+        if run_flag {
+            if self.min_gap_assertion {
+                self.assert_min_gap();
+            }
+            self.fire(success);
+        } else {
+            self.record_throttle();
+            throttled()
+        }
+        run_flag
+    }
+
+    /// Consumes the window shared with children under its lock, so a child on another thread can not fire
+    /// in it too. True without children and for fires that do not depend on the window, such as warmup
+    fn claim_shared_window(&self) -> bool {
+        let shared_window = match &self.maybe_shared_window {
+            None => return true,
+            Some(shared_window) => shared_window,
+        };
+        if self.in_warmup() || self.quantized_ready().is_some() {
+            return true;
+        }
+        shared_window.try_consume(self.fresh_now(), self.interval())
+    }
+
+    /// Debug asserts that a fire decided by the interval check is at least one interval after the last fire
+    fn assert_min_gap(&self) {
+        if self.in_warmup() || self.quantized {
+            return;
+        }
+        if let Some(last_time) = self.last_called_time() {
+            let gap = self.fresh_now().duration_since(last_time);
+            debug_assert!(
+                gap >= self.interval(),
+                "{} fired {:?} after the last fire, less than {:?}",
+                self.event_name,
+                gap,
+                self.interval()
+            );
+        }
+    }
+
+    fn fire(&mut self, success: &mut dyn FnMut()) {
+        self.cooling_down = false;
+        self.record_fire(self.fresh_now());
+        self.maybe_last_called_system_time = Some(self.clock.system_now());
+        if self.timing {
+            let start_time = self.clock.now();
+            success();
+            self.callback_time = self
+                .callback_time
+                .saturating_add(self.clock.now().duration_since(start_time));
+        } else {
+            success();
+        }
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            event_name = self.event_name,
+            total_calls = self.total_calls,
+            "throttle fired"
+        );
+        self.notify_observers();
+    }
+
+    /// Registers `observer` to be called after every fire, e.g. for metrics or logging.
+    /// Observers must be `Send` so the timer can still move between threads
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let mut break_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Break");
+    /// break_timer.add_observer(Box::new(|timer| {
+    ///     println!("{} fired, total calls {}", timer.event_name(), timer.total_calls())
+    /// }));
+    /// break_timer.run(&mut || {});
+    /// ```
+    pub fn add_observer(&mut self, observer: Box<dyn FnMut(&ThrottleTimer) + Send>) {
+        self.observers.get_mut().push(observer);
+    }
+
+    fn notify_observers(&mut self) {
+        let mut observers = std::mem::take(self.observers.get_mut());
+        for observer in observers.iter_mut() {
+            observer(self);
+        }
+        *self.observers.get_mut() = observers;
+    }
+
+    fn record_fire(&mut self, fired_time: Instant) {
+        if let Some(last_time) = self.maybe_last_called_time {
+            let interval = fired_time.duration_since(last_time);
+            if self.idle_expired(interval) {
+                self.clear_interval_stats();
+                self.maybe_first_called_time = Some(fired_time);
+                self.calls_since_first = 0;
+            } else {
+                self.record_interval(interval);
+            }
+        }
+        if self.total_calls == 0 {
+            self.maybe_first_called_time = Some(fired_time);
+        }
+        self.set_last_called_time(Some(fired_time));
+        self.maybe_last_fired_time = Some(fired_time);
+        self.total_calls = self.total_calls.saturating_add(1);
+        self.calls_since_first = self.calls_since_first.saturating_add(1);
+        self.throttled_streak = 0;
+        if self.history_capacity > 0 {
+            if self.history.len() == self.history_capacity {
+                self.history.pop_front();
+            }
+            self.history.push_back(fired_time);
+        }
+    }
+
+    fn idle_expired(&self, idle: Duration) -> bool {
+        self.idle_reset
+            .and_then(|idle_multiple| self.max_frequency.checked_mul(idle_multiple))
+            .is_some_and(|threshold| idle > threshold)
+    }
+
+    fn clear_interval_stats(&mut self) {
+        self.min_interval = None;
+        self.max_interval = None;
+        self.interval_count = 0;
+        self.interval_total = Duration::from_secs(0);
+        if let Some(histogram) = &mut self.interval_histogram {
+            histogram.clear();
+        }
+    }
+
+    fn record_interval(&mut self, interval: Duration) {
+        self.min_interval = Some(self.min_interval.map_or(interval, |min| min.min(interval)));
+        self.max_interval = Some(self.max_interval.map_or(interval, |max| max.max(interval)));
+        self.interval_count = self.interval_count.saturating_add(1);
+        self.interval_total = self.interval_total.saturating_add(interval);
+        if let Some(histogram) = &mut self.interval_histogram {
+            histogram.record(interval);
+        }
+    }
+
+    /// Fire times kept by `with_history`, oldest first
+    pub fn history(&self) -> impl Iterator<Item = Instant> + '_ {
+        self.history.iter().copied()
+    }
+
+    /// Rough estimate in bytes of the memory used by the timer, including the capacity of
+    /// internal buffers such as the fire time history. Allocator overhead is not counted
+    pub fn approx_memory_footprint(&self) -> usize {
+        size_of::<Self>()
+            + self
+                .maybe_shared_window
+                .as_ref()
+                .map_or(0, SharedWindow::approx_memory_footprint)
+            + self.history.capacity() * size_of::<Instant>()
+            + self.budget_spent.capacity() * size_of::<(Instant, Duration)>()
+            + self.observers.capacity() * size_of::<Observer>()
+            + self
+                .interval_histogram
+                .as_ref()
+                .map_or(0, Histogram::approx_memory_footprint)
+    }
+
+    /// Empties the fire time history, leaving counters and the window untouched
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    fn record_throttle(&mut self) {
+        self.throttled_calls = self.throttled_calls.saturating_add(1);
+        self.throttled_streak = self.throttled_streak.saturating_add(1);
+    }
+
+    /// Combines `other`'s counters, callback time and interval stats into this timer, keeping the later last call time.
+    /// Used to merge the same event tracked in different places, e.g. by `ThrottleRegistry::extend`.
+    ///
+    /// Interval histograms are merged when both timers have one with the same bounds.
+    /// Otherwise `other`'s histogram counts are dropped
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let mut email_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Email");
+    /// let mut other_email_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Email");
+    /// email_timer.run(&mut || {});
+    /// other_email_timer.run(&mut || {});
+    ///
+    /// email_timer.absorb(&other_email_timer);
+    /// assert_eq!(email_timer.total_calls(), &2);
+    /// ```
+    pub fn absorb(&mut self, other: &ThrottleTimer) {
+        self.total_calls = self.total_calls.saturating_add(other.total_calls);
+        self.calls_since_first = self
+            .calls_since_first
+            .saturating_add(other.calls_since_first);
+        self.throttled_calls = self.throttled_calls.saturating_add(other.throttled_calls);
+        self.success_count = self.success_count.saturating_add(other.success_count);
+        self.failure_count = self.failure_count.saturating_add(other.failure_count);
+        self.callback_time = self.callback_time.saturating_add(other.callback_time);
+        self.interval_count = self.interval_count.saturating_add(other.interval_count);
+        self.interval_total = self.interval_total.saturating_add(other.interval_total);
+        self.min_interval = merge_option(self.min_interval, other.min_interval, Duration::min);
+        self.max_interval = merge_option(self.max_interval, other.max_interval, Duration::max);
+        if let (Some(histogram), Some(other_histogram)) =
+            (&self.interval_histogram, &other.interval_histogram)
+        {
+            if let Ok(merged) = Histogram::merge(&[histogram, other_histogram]) {
+                self.interval_histogram = Some(merged);
+            }
+        }
+        self.maybe_first_called_time = merge_option(
+            self.maybe_first_called_time,
+            other.maybe_first_called_time,
+            Instant::min,
+        );
+        let window_last_called_time = merge_option(
+            self.last_called_time(),
+            other.last_called_time(),
+            Instant::max,
+        );
+        self.maybe_last_called_time = merge_option(
+            self.maybe_last_called_time,
+            other.maybe_last_called_time,
+            Instant::max,
+        );
+        self.maybe_last_fired_time = merge_option(
+            self.maybe_last_fired_time,
+            other.maybe_last_fired_time,
+            Instant::max,
+        );
+        if let Some(shared_window) = &self.maybe_shared_window {
+            shared_window.set(window_last_called_time);
+        }
+        self.maybe_last_called_system_time = merge_option(
+            self.maybe_last_called_system_time,
+            other.maybe_last_called_system_time,
+            SystemTime::max,
+        );
+    }
+
+    /// Folds past fire times into the timer as if they had been run, updating the last call time,
+    /// `total_calls` and interval stats. `fires` should be in chronological order
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let mut break_timer = ThrottleTimer::new(Duration::from_secs(10_u64), "Break");
+    /// break_timer.replay(&[Instant::now()]);
+    /// assert_eq!(break_timer.total_calls(), &1);
+    /// assert!(!break_timer.run(&mut || {}));
+    /// ```
+    pub fn replay(&mut self, fires: &[Instant]) {
+        for &fired_time in fires {
+            self.record_fire(fired_time);
+        }
+        if let Some(&last_time) = fires.last() {
+            let since_last = self.now().duration_since(last_time);
+            self.maybe_last_called_system_time = self.clock.system_now().checked_sub(since_last);
+        }
+    }
+
+    /// Runs work that costs `cost` if it fits in the time budget of the current rolling window.
+    /// Like every run, it never fires during the startup delay or a suspension.
+    /// Without a budget set by `with_time_budget` this behaves like `run` and `cost` is ignored
+    pub fn run_costed(&mut self, cost: Duration, success: &mut dyn FnMut()) -> bool {
+        let time_budget = match self.time_budget {
+            None => return self.run(success),
+            Some(time_budget) => time_budget,
+        };
+        if self.blocked_wait_time() != Duration::from_secs(0) {
+            self.record_throttle();
+            return false;
+        }
+        let now = self.now();
+        let run_flag = self
+            .budget_spent_at(now)
+            .checked_add(cost)
+            .is_some_and(|spent| spent <= time_budget);
+        if run_flag {
+            self.budget_spent.push_back((self.fresh_now(), cost));
+            self.fire(success);
+        } else {
+            self.record_throttle();
+        }
+        run_flag
+    }
+
+    /// Budget left in the current rolling window before `run_costed` rejects work, e.g. for a scheduler
+    /// deciding whether to dispatch a costly task now. `Duration::MAX` without a budget set by `with_time_budget`
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let mut cpu_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Cpu")
+    ///     .with_time_budget(Duration::from_millis(100_u64));
+    /// cpu_timer.run_costed(Duration::from_millis(30_u64), &mut || {});
+    /// assert_eq!(cpu_timer.remaining_budget(), Duration::from_millis(70_u64));
+    /// ```
+    pub fn remaining_budget(&self) -> Duration {
+        let time_budget = match self.time_budget {
+            None => return Duration::MAX,
+            Some(time_budget) => time_budget,
+        };
+        let now = self.now();
+        let spent: Duration = self
+            .budget_spent
+            .iter()
+            .filter(|&&(spent_time, _)| now.duration_since(spent_time) < self.max_frequency)
+            .map(|&(_, cost)| cost)
+            .sum();
+        time_budget.saturating_sub(spent)
+    }
+
+    /// Drops costs that left the rolling window and sums the rest
+    fn budget_spent_at(&mut self, now: Instant) -> Duration {
+        let window = self.max_frequency;
+        while let Some(&(spent_time, _)) = self.budget_spent.front() {
+            if now.duration_since(spent_time) < window {
+                break;
+            }
+            self.budget_spent.pop_front();
+        }
+        self.budget_spent.iter().map(|&(_, cost)| cost).sum()
+    }
+
+    /// Calling ```run()``` will check the last call time. If max frequency time has not passed the fn will return false.
+    /// If max_frequency duration has passed since the last call then the fn will return true
+    pub fn run(&mut self, success: &mut dyn FnMut()) -> bool {
+        self.run_throttle_cb(success, &mut || {})
+    }
+
+    /// Same as run but `success` returns whether it succeeded. After a failure the next fire
+    /// waits for the cooldown set by `with_cooldown` instead of `max_frequency`
+    pub fn run_with_cooldown(&mut self, success: &mut dyn FnMut() -> bool) -> bool {
+        let mut succeeded = true;
+        let did_run = self.run(&mut || succeeded = success());
+        if did_run {
+            self.cooling_down = !succeeded;
+        }
+        did_run
+    }
+
+    /// Consumes the window and returns `Ready` if it is open, otherwise `Pending`.
+    /// For hand written futures, which should arrange to be woken at `ready_at` when `Pending`
+    ///
+    /// # Example
+    /// ```
+    /// use std::task::Poll;
+    /// use std::time::Duration;
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let mut send_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Send");
+    /// assert_eq!(send_timer.poll_run(), Poll::Ready(()));
+    /// assert_eq!(send_timer.poll_run(), Poll::Pending);
+    /// ```
+    pub fn poll_run(&mut self) -> Poll<()> {
+        if self.run(&mut || {}) {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+
+    /// Same as run but only fires with the given `probability` when the window is open, for sampling
+    /// high volume events. A failed roll does not consume the window so the next attempt can try again.
+    /// `probability` is clamped to `0.0..=1.0` and a NaN probability never fires.
+    /// Uses a small built in pseudo random generator that is not suitable for anything security related
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let mut trace_timer = ThrottleTimer::new(Duration::from_millis(10_u64), "Trace");
+    /// // roughly 10% of allowed fires are traced
+    /// trace_timer.run_sampled(0.1, &mut || {});
+    /// ```
+    pub fn run_sampled(&mut self, probability: f64, success: &mut dyn FnMut()) -> bool {
+        let probability = if probability.is_nan() {
+            0.0
+        } else {
+            probability.clamp(0.0, 1.0)
+        };
+        if !self.can_run() || self.next_random() >= probability {
+            self.record_throttle();
+            return false;
+        }
+        self.run(success)
+    }
+
+    /// xorshift64* returning a value in `0.0..1.0`
+    fn next_random(&mut self) -> f64 {
+        let mut x = self.rng_state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.rng_state = x;
+        (x.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 11) as f64 / (1_u64 << 53) as f64
+    }
+
+    /// Same as run but tallies the result of `success`, see `success_count` and `failure_count`.
+    /// The window is consumed on every fire whatever the result
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let mut upload_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Upload");
+    /// upload_timer.run_tallied(&mut || Err(()));
+    /// assert_eq!(upload_timer.failure_count(), 1);
+    /// ```
+    pub fn run_tallied(&mut self, success: &mut dyn FnMut() -> Result<(), ()>) -> bool {
+        let mut result = Ok(());
+        let did_run = self.run(&mut || result = success());
+        if did_run {
+            match result {
+                Ok(()) => self.success_count = self.success_count.saturating_add(1),
+                Err(()) => self.failure_count = self.failure_count.saturating_add(1),
+            }
+        }
+        did_run
+    }
+
+    /// Fires now returning `Ok`, or returns the instant to retry at as `Err`.
+    /// Combines `run` and `ready_at` in one call for event loops
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let mut flush_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Flush");
+    /// assert_eq!(flush_timer.fire_or_deadline(&mut || {}), Ok(()));
+    /// if let Err(retry_at) = flush_timer.fire_or_deadline(&mut || {}) {
+    ///     println!("retry at {:?}", retry_at);
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    /// In the same cases as `ready_at`. See `try_fire_or_deadline`
+    pub fn fire_or_deadline(&mut self, success: &mut dyn FnMut()) -> Result<(), Instant> {
+        match self.try_fire_or_deadline(success) {
+            Ok(fired_or_deadline) => fired_or_deadline,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Same as `fire_or_deadline` but returns `Error::TimeOverflow` instead of panicking
+    /// when the timer did not fire and the instant to retry at can not be represented
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::{Error, ThrottleTimer};
+    ///
+    /// let mut never_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Never")
+    ///     .with_startup_delay(Duration::MAX);
+    /// assert_eq!(never_timer.try_fire_or_deadline(&mut || {}), Err(Error::TimeOverflow));
+    /// ```
+    pub fn try_fire_or_deadline(
+        &mut self,
+        success: &mut dyn FnMut(),
+    ) -> Result<Result<(), Instant>, Error> {
+        if self.run(success) {
+            Ok(Ok(()))
+        } else {
+            self.try_ready_at().map(Err)
+        }
+    }
+
+    /// Same as run but will never fire once `deadline` has passed
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use throttle_timer::ThrottleTimer;
+    ///
+    /// let mut heartbeat_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Heartbeat");
+    /// let shutdown = Instant::now() + Duration::from_secs(60_u64);
+    /// assert!(heartbeat_timer.run_before(shutdown, &mut || {}));
+    /// ```
+    pub fn run_before(&mut self, deadline: Instant, success: &mut dyn FnMut()) -> bool {
+        if self.now() > deadline {
+            self.record_throttle();
+            return false;
+        }
+        self.run(success)
+    }
+
+    /// Calling ```run()``` will check the last call time. If max frequency time has not passed the fn will return false.
+    /// If max_frequency duration has passed since the last call then the fn will return true
+    ///
+    /// The sleep is capped at `max_frequency` so a clock anomaly can not block the thread indefinitely.
+    /// If the timer is still not ready after the capped sleep, e.g. while suspended, it does not fire
+    pub fn run_wait(&mut self, success: &mut dyn FnMut()) {
+        self.clock.sleep(self.wait_time().min(self.max_frequency));
+        self.run_throttle_cb(success, &mut || {});
+    }
+
+    // Same as run but will print a message if throttled
+    pub fn run_with_msg(&mut self, success: &mut dyn FnMut()) -> bool {
+        let did_run = self.run(success);
+        if !did_run {
+            match self.last_called_time() {
+                Some(last_time) => print_line(format_args!(
+                    "{}{} throttled, last time {:?}",
+                    self.prefix,
+                    self.event_name(),
+                    self.now().duration_since(last_time)
+                )),
+                None => print_line(format_args!(
+                    "{}{} throttled, never called",
+                    self.prefix,
+                    self.event_name()
+                )),
+            }
+        }
+        did_run
+    }
+}
+
+/// Runs every timer in `timers` that is ready, calling `f` with its index. Throttled timers are skipped
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use throttle_timer::{run_ready, ThrottleTimer};
+///
+/// let mut timers = [
+///     ThrottleTimer::new(Duration::from_secs(1_u64), "Heartbeat"),
+///     ThrottleTimer::new(Duration::from_secs(60_u64), "Report"),
+/// ];
+/// run_ready(&mut timers, |index| println!("timer {} fired", index));
+/// assert!(timers.iter().all(|timer| timer.total_calls() == &1));
+/// ```
+pub fn run_ready(timers: &mut [ThrottleTimer], f: impl Fn(usize)) {
+    for (index, timer) in timers.iter_mut().enumerate() {
+        timer.run(&mut || f(index));
+    }
+}
+
+/// Stats line printed by `print_stats`
+impl fmt::Display for ThrottleTimer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let created_time_elapsed = self.uptime().unwrap_or_default();
+        if self.color {
+            f.write_str(self.stats_color())?;
+        }
+        write!(
+            f,
+            "{}{} called {:.2}/sec, total calls {}, has been running for {:?}",
+            self.prefix,
+            self.event_name,
+            rate(self.total_calls, created_time_elapsed),
+            self.total_calls,
+            created_time_elapsed,
+        )?;
+        if self.color {
+            f.write_str("\x1b[0m")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        run_ready, ChildThrottle, Clock, Error, IntervalStats, MockClock, Stats, StatsDelta,
+        ThrottleState, ThrottleTimer,
+    };
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        task::Poll,
+        thread,
+        time::{Duration, UNIX_EPOCH},
+    };
+
+    #[test]
+    fn test_run() {
+        let mut break_timer: ThrottleTimer =
+            ThrottleTimer::new(Duration::from_secs(45_000_u64), "Break");
+        let run_flag = break_timer.run(&mut || {});
+
+        // timers always run when no previous runs
+        assert!(run_flag);
+        if run_flag {
+            println!("timer do run flag is set to true")
+        }
+        break_timer.event_name();
+        break_timer.total_calls();
+        break_timer.max_frequency();
+        break_timer.created_date();
+    }
+
+    #[test]
+    fn test_run_with_msg() {
+        let mut break_timer: ThrottleTimer =
+            ThrottleTimer::new(Duration::from_secs(45_000_u64), "Break");
+        let run_flag = break_timer.run_with_msg(&mut || {});
+
+        // timers always run when no previous runs
+        assert!(run_flag);
+    }
+
+    #[test]
+    fn test_call_count() {
+        let mut break_timer: ThrottleTimer =
+            ThrottleTimer::new(Duration::from_nanos(1_u64), "Break");
+
+        for _ in 0..100 {
+            assert!(break_timer.run(&mut || {}));
+            thread::sleep(Duration::from_nanos(100_u64));
+        }
+
+        // timers always run when no previous runs
+        assert_eq!(break_timer.total_calls, 100);
+        break_timer.print_stats();
+    }
+
+    #[test]
+    fn test_can_run() {
+        let mut break_timer: ThrottleTimer =
+            ThrottleTimer::new(Duration::from_secs(1_u64), "Break");
+
+        assert!(break_timer.run(&mut || {}));
+        for _ in 0..100 {
+            assert!(!break_timer.can_run());
+            assert!(!break_timer.run(&mut || {}));
+        }
+
+        // timers always run when no previous runs
+        assert_eq!(break_timer.total_calls, 1);
+        break_timer.print_stats();
+    }
+
+    #[test]
+    fn test_print_debug() {
+        println!(
+            "{:?}",
+            ThrottleTimer::new(Duration::from_nanos(1_u64), "Break")
+        );
+    }
+
+    #[test]
+    fn test_in_loop() {
+        let mut break_timer = ThrottleTimer::new(Duration::from_secs(10_u64), "Break");
+
+        // timers always run when no previous runs
+        assert!(break_timer.run(&mut || {}));
+        for _ in 0..100 {
+            // timer will not run as 10 secs has not passed
+            // do run will return false
+            assert!(!break_timer.run(&mut || {}));
+        }
+        assert_eq!(break_timer.total_calls(), &1);
+    }
+
+    #[test]
+    fn test_run_wait() {
+        let mut break_timer = ThrottleTimer::new(Duration::from_nanos(10_u64), "Break");
+
+        break_timer.run_wait(&mut || {});
+        break_timer.run_wait(&mut || {});
+        break_timer.run_wait(&mut || {});
+        assert_eq!(break_timer.total_calls(), &3);
+    }
+
+    #[test]
+    fn test_with_delay() {
+        let mut snack_timer: ThrottleTimer =
+            ThrottleTimer::new(Duration::from_secs(1_u64), "Snack");
+        let run_flag = snack_timer.run(&mut || {});
+
+        // timers always run when no previous runs
+        assert!(run_flag);
+
+        let run_flag2 = snack_timer.run_with_msg(&mut || {});
+
+        // run flag false as no time has passed
+        assert!(!run_flag2);
+
+        thread::sleep(snack_timer.max_frequency);
+        assert!(snack_timer.run(&mut || {}));
+        thread::sleep(Duration::from_millis(100_u64));
+        assert!(!snack_timer.run(&mut || {}));
+        thread::sleep(Duration::from_secs(1_u64));
         assert!(snack_timer.run(&mut || {}));
     }
+
+    #[test]
+    fn test_quantized() {
+        let clock = MockClock::at(UNIX_EPOCH + Duration::from_millis(10_300_u64));
+        let mut log_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Log")
+            .with_clock(clock.clone())
+            .with_quantized(true);
+
+        // timers always run when no previous runs
+        assert!(log_timer.run(&mut || {}));
+        clock.advance(Duration::from_millis(500_u64));
+        assert!(!log_timer.run(&mut || {}));
+        assert_eq!(log_timer.wait_time(), Duration::from_millis(200_u64));
+
+        // 10.999s is still in the same second
+        clock.advance(Duration::from_millis(199_u64));
+        assert!(!log_timer.run(&mut || {}));
+
+        // fires at the boundary even though less than max_frequency passed since the last fire
+        clock.advance(Duration::from_millis(1_u64));
+        assert!(log_timer.run(&mut || {}));
+        clock.advance(Duration::from_millis(500_u64));
+        assert!(!log_timer.run(&mut || {}));
+        clock.advance(Duration::from_millis(500_u64));
+        assert!(log_timer.run(&mut || {}));
+        assert_eq!(log_timer.total_calls(), &3);
+    }
+
+    #[test]
+    fn test_try_print_stats_clock_went_backwards() {
+        let clock = MockClock::at(UNIX_EPOCH + Duration::from_secs(60_u64));
+        let mut break_timer =
+            ThrottleTimer::new(Duration::from_secs(1_u64), "Break").with_clock(clock.clone());
+        assert!(break_timer.run(&mut || {}));
+        assert_eq!(break_timer.try_print_stats(), Ok(()));
+
+        clock.set_system_time(UNIX_EPOCH + Duration::from_secs(50_u64));
+        assert_eq!(
+            break_timer.try_print_stats(),
+            Err(Error::ClockWentBackwards(Duration::from_secs(10_u64)))
+        );
+        break_timer.print_stats();
+    }
+
+    #[test]
+    fn test_warmup() {
+        let mut cache_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Cache")
+            .with_clock(MockClock::new())
+            .with_warmup(3);
+
+        for _ in 0..3 {
+            assert!(cache_timer.run(&mut || {}));
+        }
+        assert!(!cache_timer.can_run());
+        assert!(!cache_timer.run(&mut || {}));
+        assert_eq!(cache_timer.total_calls(), &3);
+    }
+
+    #[test]
+    fn test_run_costed() {
+        let clock = MockClock::new();
+        let mut cpu_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Cpu")
+            .with_clock(clock.clone())
+            .with_time_budget(Duration::from_millis(100_u64));
+
+        assert!(cpu_timer.run_costed(Duration::from_millis(40_u64), &mut || {}));
+        clock.advance(Duration::from_millis(300_u64));
+        assert!(cpu_timer.run_costed(Duration::from_millis(40_u64), &mut || {}));
+        clock.advance(Duration::from_millis(300_u64));
+        assert!(cpu_timer.run_costed(Duration::from_millis(20_u64), &mut || {}));
+
+        // budget exhausted
+        assert!(!cpu_timer.run_costed(Duration::from_millis(1_u64), &mut || {}));
+        assert_eq!(cpu_timer.total_calls(), &3);
+
+        // first cost leaves the window
+        clock.advance(Duration::from_millis(400_u64));
+        assert!(cpu_timer.run_costed(Duration::from_millis(40_u64), &mut || {}));
+        assert!(!cpu_timer.run_costed(Duration::from_millis(1_u64), &mut || {}));
+    }
+
+    #[test]
+    fn test_new_child() {
+        let clock = MockClock::new();
+        let mut parent =
+            ThrottleTimer::new(Duration::from_secs(1_u64), "Alerts").with_clock(clock.clone());
+        let mut email = parent.new_child("Email");
+        let mut sms = parent.new_child("Sms");
+
+        assert!(email.run(&mut || {}));
+        assert!(!email.run(&mut || {}));
+        assert!(!sms.run(&mut || {}));
+        assert!(!parent.run(&mut || {}));
+
+        clock.advance(Duration::from_secs(1_u64));
+        assert!(sms.run(&mut || {}));
+        assert!(!email.can_run());
+        assert_eq!(email.total_calls(), &1);
+        assert_eq!(sms.total_calls(), &1);
+        assert_eq!(parent.total_calls(), &0);
+    }
+
+    #[test]
+    fn test_new_child_threads() {
+        let clock = MockClock::new();
+        let mut parent =
+            ThrottleTimer::new(Duration::from_secs(1_u64), "Alerts").with_clock(clock.clone());
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let mut child = parent.new_child("Child");
+                thread::spawn(move || {
+                    for _ in 0..1_000 {
+                        child.run(&mut || {});
+                    }
+                    *child.total_calls()
+                })
+            })
+            .collect();
+        let child_calls: usize = handles.into_iter().map(|h| h.join().unwrap()).sum();
+        assert_eq!(child_calls, 1);
+        assert!(!parent.run(&mut || {}));
+    }
+
+    #[test]
+    fn test_new_child_stats() {
+        let clock = MockClock::new();
+        let mut parent =
+            ThrottleTimer::new(Duration::from_secs(1_u64), "Alerts").with_clock(clock.clone());
+        let mut email = parent.new_child("Email");
+
+        // a child fire before the parent's first fire is not an interval of the parent
+        assert!(email.run(&mut || {}));
+        clock.advance(Duration::from_secs(5_u64));
+        assert!(parent.run(&mut || {}));
+        assert_eq!(parent.interval_stats(), None);
+
+        clock.advance(Duration::from_secs(10_u64));
+        assert!(parent.run(&mut || {}));
+        // and neither is a child fire after the parent's last fire
+        clock.advance(Duration::from_secs(100_u64));
+        assert!(email.run(&mut || {}));
+        assert!(!parent.is_ready());
+
+        assert_eq!(parent.total_calls(), &2);
+        assert_eq!(parent.average_interval(), Some(Duration::from_secs(10_u64)));
+        assert_eq!(parent.interval_stats().unwrap().samples, 1);
+        assert_eq!(
+            parent.interval_stats().unwrap().max,
+            Duration::from_secs(10_u64)
+        );
+    }
+
+    #[test]
+    fn test_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<ThrottleTimer>();
+        assert_send_sync::<ChildThrottle>();
+
+        // observers only need to be Send
+        let mut break_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Break");
+        let fires = std::cell::Cell::new(0_usize);
+        break_timer.add_observer(Box::new(move |_| fires.set(fires.get() + 1)));
+        let handle = thread::spawn(move || break_timer.run(&mut || {}));
+        assert!(handle.join().unwrap());
+    }
+
+    #[test]
+    fn test_clamp_frequency() {
+        let min = Duration::from_secs(1_u64);
+        let max = Duration::from_secs(60_u64);
+        let mut poll_timer = ThrottleTimer::new(Duration::from_secs(3_600_u64), "Poll");
+
+        assert_eq!(poll_timer.clamp_frequency(min, max), max);
+        assert_eq!(poll_timer.max_frequency(), &max);
+
+        poll_timer.set_max_frequency(Duration::from_secs(0_u64));
+        assert_eq!(poll_timer.clamp_frequency(min, max), min);
+
+        poll_timer.set_max_frequency(Duration::from_secs(30_u64));
+        assert_eq!(
+            poll_timer.clamp_frequency(min, max),
+            Duration::from_secs(30_u64)
+        );
+    }
+
+    #[test]
+    fn test_run_before() {
+        let clock = MockClock::new();
+        let mut heartbeat_timer =
+            ThrottleTimer::new(Duration::from_secs(1_u64), "Heartbeat").with_clock(clock.clone());
+        let deadline = clock.now();
+
+        // the deadline itself is still allowed
+        assert!(heartbeat_timer.run_before(deadline, &mut || {}));
+
+        clock.advance(Duration::from_secs(1_u64));
+        assert!(heartbeat_timer.can_run());
+        assert!(!heartbeat_timer.run_before(deadline, &mut || {}));
+        assert_eq!(heartbeat_timer.total_calls(), &1);
+    }
+
+    #[test]
+    fn test_replay() {
+        let clock = MockClock::new();
+        let mut break_timer =
+            ThrottleTimer::new(Duration::from_secs(10_u64), "Break").with_clock(clock.clone());
+        let start = clock.now();
+        clock.advance(Duration::from_secs(60_u64));
+
+        assert_eq!(break_timer.min_interval(), None);
+        break_timer.replay(&[
+            start,
+            start + Duration::from_secs(15_u64),
+            start + Duration::from_secs(55_u64),
+        ]);
+
+        assert_eq!(break_timer.total_calls(), &3);
+        assert_eq!(
+            break_timer.min_interval(),
+            Some(Duration::from_secs(15_u64))
+        );
+        assert_eq!(
+            break_timer.max_interval(),
+            Some(Duration::from_secs(40_u64))
+        );
+        // last replayed fire was 5s ago
+        assert_eq!(break_timer.wait_time(), Duration::from_secs(5_u64));
+        assert!(!break_timer.run(&mut || {}));
+    }
+
+    #[test]
+    fn test_countdown() {
+        let clock = MockClock::new();
+        let mut break_timer =
+            ThrottleTimer::new(Duration::from_secs(10_u64), "Break").with_clock(clock.clone());
+        assert_eq!(break_timer.countdown(), Duration::from_secs(0_u64));
+
+        assert!(break_timer.run(&mut || {}));
+        clock.advance(Duration::from_secs(4_u64));
+        assert_eq!(break_timer.countdown(), Duration::from_secs(6_u64));
+
+        for _ in 0..10 {
+            break_timer.run(&mut || {});
+            clock.advance(Duration::from_millis(700_u64));
+            assert!(break_timer.countdown() <= *break_timer.max_frequency());
+        }
+
+        break_timer.set_max_frequency(Duration::from_secs(2_u64));
+        assert!(break_timer.countdown() <= Duration::from_secs(2_u64));
+    }
+
+    #[test]
+    fn test_add_observer() {
+        let mut break_timer = ThrottleTimer::new(Duration::from_secs(10_u64), "Break");
+        let metrics_count = Arc::new(AtomicUsize::new(0));
+        let log_count = Arc::new(AtomicUsize::new(0));
+
+        let metrics = Arc::clone(&metrics_count);
+        break_timer.add_observer(Box::new(move |timer| {
+            metrics.store(*timer.total_calls(), Ordering::Relaxed)
+        }));
+        let log = Arc::clone(&log_count);
+        break_timer.add_observer(Box::new(move |_| {
+            log.fetch_add(1, Ordering::Relaxed);
+        }));
+
+        assert!(break_timer.run(&mut || {}));
+        assert!(!break_timer.run(&mut || {}));
+        assert_eq!(metrics_count.load(Ordering::Relaxed), 1);
+        assert_eq!(log_count.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_simulate() {
+        let start = std::time::Instant::now();
+        let at = |millis: u64| start + Duration::from_millis(millis);
+        let attempts = [
+            at(0),
+            at(100),
+            at(999),
+            at(1_000),
+            at(1_500),
+            at(2_100),
+            at(2_200),
+            at(3_099),
+            at(3_100),
+        ];
+
+        assert_eq!(
+            ThrottleTimer::simulate(Duration::from_secs(1_u64), &attempts),
+            vec![true, false, false, true, false, true, false, false, true]
+        );
+        assert_eq!(
+            ThrottleTimer::simulate(Duration::from_secs(0_u64), &attempts[..3]),
+            vec![true, true, true]
+        );
+        assert!(ThrottleTimer::simulate(Duration::from_secs(1_u64), &[]).is_empty());
+    }
+
+    #[test]
+    fn test_suspend_until() {
+        let clock = MockClock::new();
+        let mut sync_timer =
+            ThrottleTimer::new(Duration::from_millis(10_u64), "Sync").with_clock(clock.clone());
+        sync_timer.suspend_until(clock.now() + Duration::from_millis(100_u64));
+
+        assert!(!sync_timer.run_with_msg(&mut || {}));
+        assert_eq!(sync_timer.wait_time(), Duration::from_millis(100_u64));
+        clock.advance(Duration::from_millis(99_u64));
+        assert!(!sync_timer.run(&mut || {}));
+
+        clock.advance(Duration::from_millis(1_u64));
+        assert!(sync_timer.run(&mut || {}));
+        assert!(!sync_timer.run(&mut || {}));
+        assert_eq!(sync_timer.total_calls(), &1);
+
+        let mut cpu_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Cpu")
+            .with_clock(clock.clone())
+            .with_time_budget(Duration::from_millis(100_u64));
+        cpu_timer.suspend_until(clock.now() + Duration::from_secs(100_u64));
+        assert!(!cpu_timer.run_costed(Duration::from_millis(1_u64), &mut || {}));
+        assert_eq!(cpu_timer.remaining_budget(), Duration::from_millis(100_u64));
+        clock.advance(Duration::from_secs(100_u64));
+        assert!(cpu_timer.run_costed(Duration::from_millis(1_u64), &mut || {}));
+    }
+
+    #[test]
+    fn test_interval_stats() {
+        let clock = MockClock::new();
+        let mut break_timer =
+            ThrottleTimer::new(Duration::from_secs(1_u64), "Break").with_clock(clock.clone());
+
+        assert!(break_timer.run(&mut || {}));
+        assert_eq!(break_timer.interval_stats(), None);
+        clock.advance(Duration::from_secs(2_u64));
+        assert!(break_timer.run(&mut || {}));
+        clock.advance(Duration::from_secs(4_u64));
+        assert!(break_timer.run(&mut || {}));
+
+        assert_eq!(
+            break_timer.interval_stats(),
+            Some(IntervalStats {
+                min: Duration::from_secs(2_u64),
+                avg: Duration::from_secs(3_u64),
+                max: Duration::from_secs(4_u64),
+                samples: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn test_open_window() {
+        let clock = MockClock::new();
+        let mut break_timer =
+            ThrottleTimer::new(Duration::from_secs(60_u64), "Break").with_clock(clock.clone());
+        assert!(break_timer.run(&mut || {}));
+        clock.advance(Duration::from_secs(60_u64));
+        assert!(break_timer.run(&mut || {}));
+        assert!(!break_timer.run(&mut || {}));
+
+        break_timer.open_window();
+        assert_eq!(break_timer.total_calls(), &2);
+        assert!(break_timer.run(&mut || {}));
+        assert!(!break_timer.run(&mut || {}));
+        assert_eq!(break_timer.total_calls(), &3);
+        assert_eq!(break_timer.interval_stats().unwrap().samples, 1);
+    }
+
+    #[test]
+    fn test_run_with_cooldown() {
+        let clock = MockClock::new();
+        let mut connect_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Connect")
+            .with_clock(clock.clone())
+            .with_cooldown(Duration::from_secs(5_u64));
+
+        assert!(connect_timer.run_with_cooldown(&mut || true));
+        clock.advance(Duration::from_secs(1_u64));
+        assert!(connect_timer.run_with_cooldown(&mut || false));
+        assert_eq!(connect_timer.wait_time(), Duration::from_secs(5_u64));
+
+        clock.advance(Duration::from_secs(1_u64));
+        assert!(!connect_timer.run_with_cooldown(&mut || true));
+        clock.advance(Duration::from_secs(4_u64));
+        assert!(connect_timer.run_with_cooldown(&mut || true));
+
+        // back to max_frequency after a success
+        clock.advance(Duration::from_secs(1_u64));
+        assert!(connect_timer.can_run());
+    }
+
+    #[test]
+    fn test_total_attempts() {
+        let clock = MockClock::new();
+        let mut break_timer =
+            ThrottleTimer::new(Duration::from_secs(1_u64), "Break").with_clock(clock.clone());
+
+        for _ in 0..3 {
+            assert!(break_timer.run(&mut || {}));
+            assert!(!break_timer.run(&mut || {}));
+            assert!(!break_timer.run(&mut || {}));
+            clock.advance(Duration::from_secs(1_u64));
+        }
+
+        assert_eq!(break_timer.throttled_calls(), &6);
+        assert_eq!(break_timer.total_attempts(), 9);
+        assert_eq!(
+            break_timer.stats(),
+            Stats {
+                event_name: "Break",
+                total_calls: 3,
+                throttled_calls: 6,
+                total_attempts: 9,
+                uptime: Duration::from_secs(3_u64),
+            }
+        );
+    }
+
+    #[test]
+    fn test_resuming() {
+        let now = std::time::Instant::now();
+        let mut break_timer = ThrottleTimer::resuming(
+            Duration::from_secs(60_u64),
+            "Break",
+            now - Duration::from_secs(20_u64),
+            5,
+        );
+
+        assert!(!break_timer.can_run());
+        assert!(!break_timer.run(&mut || {}));
+        assert_eq!(break_timer.total_calls(), &5);
+        assert!(break_timer.wait_time() <= Duration::from_secs(40_u64));
+        assert!(break_timer.wait_time() > Duration::from_secs(30_u64));
+
+        // future instants are clamped to now
+        let future_timer = ThrottleTimer::resuming(
+            Duration::from_secs(60_u64),
+            "Break",
+            now + Duration::from_secs(3_600_u64),
+            0,
+        );
+        assert!(future_timer.wait_time() <= Duration::from_secs(60_u64));
+    }
+
+    #[test]
+    fn test_history() {
+        let clock = MockClock::new();
+        let mut break_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Break")
+            .with_clock(clock.clone())
+            .with_history(3);
+        let mut fired_times = Vec::new();
+
+        for _ in 0..5 {
+            fired_times.push(clock.now());
+            assert!(break_timer.run(&mut || {}));
+            assert!(!break_timer.run(&mut || {}));
+            clock.advance(Duration::from_secs(1_u64));
+        }
+
+        assert_eq!(break_timer.history().collect::<Vec<_>>(), fired_times[2..]);
+        assert_eq!(
+            ThrottleTimer::new(Duration::from_secs(1_u64), "Break")
+                .history()
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_average_interval() {
+        let clock = MockClock::new();
+        let mut break_timer =
+            ThrottleTimer::new(Duration::from_secs(1_u64), "Break").with_clock(clock.clone());
+
+        // startup gap before the first fire is not counted
+        clock.advance(Duration::from_secs(100_u64));
+        assert!(break_timer.run(&mut || {}));
+        assert_eq!(break_timer.average_interval(), None);
+
+        for _ in 0..4 {
+            clock.advance(Duration::from_secs(3_u64));
+            assert!(break_timer.run(&mut || {}));
+        }
+        assert_eq!(
+            break_timer.average_interval(),
+            Some(Duration::from_secs(3_u64))
+        );
+
+        // opening the window keeps the average like the other interval stats
+        break_timer.open_window();
+        assert_eq!(
+            break_timer.average_interval(),
+            Some(Duration::from_secs(3_u64))
+        );
+        assert_eq!(break_timer.interval_stats().unwrap().samples, 4);
+    }
+
+    #[test]
+    fn test_with_color() {
+        let clock = MockClock::new();
+        let mut plain_timer =
+            ThrottleTimer::new(Duration::from_secs(1_u64), "Plain").with_clock(clock.clone());
+        let mut color_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Color")
+            .with_clock(clock)
+            .with_color(true);
+        assert!(!format!("{}", plain_timer).contains('\x1b'));
+        assert!(format!("{}", color_timer).starts_with("\x1b[32m"));
+
+        for _ in 0..20 {
+            plain_timer.run(&mut || {});
+            color_timer.run(&mut || {});
+        }
+        assert!(!format!("{}", plain_timer).contains('\x1b'));
+        let colored = format!("{}", color_timer);
+        assert!(colored.starts_with("\x1b[31m"));
+        assert!(colored.ends_with("\x1b[0m"));
+    }
+
+    #[test]
+    fn test_set_max_frequency() {
+        let clock = MockClock::new();
+        let mut poll_timer =
+            ThrottleTimer::new(Duration::from_secs(60_u64), "Poll").with_clock(clock.clone());
+        assert!(poll_timer.run(&mut || {}));
+        clock.advance(Duration::from_secs(10_u64));
+        assert!(!poll_timer.run(&mut || {}));
+
+        // 10s already passed so the shorter interval is over
+        poll_timer.set_max_frequency(Duration::from_secs(5_u64));
+        assert_eq!(poll_timer.wait_time(), Duration::from_secs(0_u64));
+        assert!(poll_timer.run(&mut || {}));
+
+        // without the reset a shortened interval still has to pass
+        poll_timer.set_max_frequency(Duration::from_secs(2_u64));
+        assert!(!poll_timer.run(&mut || {}));
+
+        let mut reset_timer = ThrottleTimer::new(Duration::from_secs(60_u64), "Reset")
+            .with_clock(clock)
+            .with_reset_on_frequency_change(true);
+        assert!(reset_timer.run(&mut || {}));
+        reset_timer.set_max_frequency(Duration::from_secs(30_u64));
+        assert!(reset_timer.run(&mut || {}));
+        assert!(!reset_timer.run(&mut || {}));
+    }
+
+    #[test]
+    fn test_poll_run() {
+        let clock = MockClock::new();
+        let mut send_timer =
+            ThrottleTimer::new(Duration::from_secs(1_u64), "Send").with_clock(clock.clone());
+
+        assert_eq!(send_timer.poll_run(), Poll::Ready(()));
+        assert_eq!(send_timer.poll_run(), Poll::Pending);
+        assert_eq!(
+            send_timer.ready_at(),
+            clock.now() + Duration::from_secs(1_u64)
+        );
+
+        clock.advance(Duration::from_millis(999_u64));
+        assert_eq!(send_timer.poll_run(), Poll::Pending);
+        clock.advance(Duration::from_millis(1_u64));
+        assert_eq!(send_timer.ready_at(), clock.now());
+        assert_eq!(send_timer.poll_run(), Poll::Ready(()));
+        assert_eq!(send_timer.total_calls(), &2);
+    }
+
+    #[test]
+    fn test_calls_per_sec() {
+        let clock = MockClock::new();
+        let mut break_timer =
+            ThrottleTimer::new(Duration::from_nanos(1_u64), "Break").with_clock(clock.clone());
+        assert_eq!(break_timer.calls_per_sec(), 0.0);
+        break_timer.print_stats();
+
+        assert!(break_timer.run(&mut || {}));
+        assert_eq!(break_timer.calls_per_sec(), 0.0);
+        assert!(format!("{}", break_timer).contains("called 0.00/sec"));
+
+        clock.advance(Duration::from_nanos(1_u64));
+        assert!(break_timer.calls_per_sec().is_finite());
+        clock.advance(Duration::from_nanos(1_999_999_999_u64));
+        assert!(break_timer.run(&mut || {}));
+        assert_eq!(break_timer.calls_per_sec(), 1.0);
+    }
+
+    #[test]
+    fn test_callback_time() {
+        let mut sleep_timer =
+            ThrottleTimer::new(Duration::from_nanos(1_u64), "Sleep").with_timing(true);
+        for _ in 0..3 {
+            thread::sleep(Duration::from_nanos(100_u64));
+            assert!(sleep_timer.run(&mut || thread::sleep(Duration::from_millis(20_u64))));
+        }
+        assert!(sleep_timer.callback_time() >= Duration::from_millis(60_u64));
+        assert!(sleep_timer.callback_time() < Duration::from_secs(1_u64));
+
+        let mut untimed_timer = ThrottleTimer::new(Duration::from_nanos(1_u64), "Untimed");
+        assert!(untimed_timer.run(&mut || thread::sleep(Duration::from_millis(20_u64))));
+        assert_eq!(untimed_timer.callback_time(), Duration::from_secs(0_u64));
+
+        untimed_timer.absorb(&sleep_timer);
+        assert_eq!(untimed_timer.callback_time(), sleep_timer.callback_time());
+    }
+
+    #[test]
+    fn test_min_gap_assertion() {
+        let clock = MockClock::new();
+        let mut strict_timer = ThrottleTimer::new(Duration::from_millis(100_u64), "Strict")
+            .with_clock(clock.clone())
+            .with_cooldown(Duration::from_millis(300_u64))
+            .with_min_gap_assertion(true);
+
+        for step in 0..1_000_u64 {
+            strict_timer.run(&mut || {});
+            if step % 7 == 0 {
+                strict_timer.run_with_cooldown(&mut || false);
+            }
+            clock.advance(Duration::from_millis(step % 13));
+        }
+        strict_timer.open_window();
+        assert!(strict_timer.run(&mut || {}));
+        assert!(strict_timer.total_calls() > &10);
+    }
+
+    #[test]
+    fn test_startup_delay() {
+        let clock = MockClock::new();
+        let mut report_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Report")
+            .with_clock(clock.clone())
+            .with_startup_delay(Duration::from_secs(30_u64));
+
+        assert!(!report_timer.run(&mut || {}));
+        assert_eq!(report_timer.wait_time(), Duration::from_secs(30_u64));
+        clock.advance(Duration::from_secs(29_u64));
+        assert!(!report_timer.run(&mut || {}));
+
+        clock.advance(Duration::from_secs(1_u64));
+        assert!(report_timer.run(&mut || {}));
+        assert!(!report_timer.run(&mut || {}));
+        assert_eq!(report_timer.total_calls(), &1);
+
+        let mut cpu_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Cpu")
+            .with_clock(clock.clone())
+            .with_startup_delay(Duration::from_secs(100_u64))
+            .with_time_budget(Duration::from_millis(100_u64));
+        assert!(!cpu_timer.run_costed(Duration::from_millis(1_u64), &mut || {}));
+        clock.advance(Duration::from_secs(100_u64));
+        assert!(cpu_timer.run_costed(Duration::from_millis(1_u64), &mut || {}));
+    }
+
+    #[test]
+    fn test_stats_since() {
+        let clock = MockClock::new();
+        let mut break_timer =
+            ThrottleTimer::new(Duration::from_secs(1_u64), "Break").with_clock(clock.clone());
+        for _ in 0..3 {
+            break_timer.run(&mut || {});
+            clock.advance(Duration::from_secs(1_u64));
+        }
+        let prev = break_timer.stats();
+
+        for _ in 0..4 {
+            break_timer.run(&mut || {});
+            break_timer.run(&mut || {});
+            clock.advance(Duration::from_secs(2_u64));
+        }
+        let now = break_timer.stats();
+
+        assert_eq!(
+            now.since(&prev),
+            StatsDelta {
+                total_calls: 4,
+                throttled_calls: 4,
+                total_attempts: 8,
+                span: Duration::from_secs(8_u64),
+                calls_per_sec: 0.5,
+            }
+        );
+        assert_eq!(now.since(&now).calls_per_sec, 0.0);
+    }
+
+    #[test]
+    fn test_throttled_streak_duration() {
+        let clock = MockClock::new();
+        let mut alert_timer =
+            ThrottleTimer::new(Duration::from_secs(60_u64), "Alert").with_clock(clock.clone());
+        assert!(alert_timer.run(&mut || {}));
+        clock.advance(Duration::from_secs(5_u64));
+        assert_eq!(
+            alert_timer.throttled_streak_duration(),
+            Duration::from_secs(0_u64)
+        );
+
+        let mut last_streak = Duration::from_secs(0_u64);
+        for _ in 0..5 {
+            assert!(!alert_timer.run(&mut || {}));
+            let streak = alert_timer.throttled_streak_duration();
+            assert!(streak > last_streak);
+            last_streak = streak;
+            clock.advance(Duration::from_secs(10_u64));
+        }
+        assert_eq!(last_streak, Duration::from_secs(45_u64));
+
+        clock.advance(Duration::from_secs(10_u64));
+        assert!(alert_timer.run(&mut || {}));
+        assert_eq!(
+            alert_timer.throttled_streak_duration(),
+            Duration::from_secs(0_u64)
+        );
+    }
+
+    #[test]
+    fn test_run_wait_bounded() {
+        let clock = MockClock::new();
+        let mut sync_timer =
+            ThrottleTimer::new(Duration::from_secs(1_u64), "Sync").with_clock(clock.clone());
+        let start = clock.now();
+        sync_timer.suspend_until(start + Duration::from_secs(3_600_u64));
+        assert_eq!(sync_timer.wait_time(), Duration::from_secs(3_600_u64));
+
+        sync_timer.run_wait(&mut || {});
+        assert_eq!(clock.now() - start, Duration::from_secs(1_u64));
+        assert_eq!(sync_timer.total_calls(), &0);
+
+        sync_timer.suspend_until(start);
+        sync_timer.run_wait(&mut || {});
+        sync_timer.run_wait(&mut || {});
+        assert_eq!(clock.now() - start, Duration::from_secs(2_u64));
+        assert_eq!(sync_timer.total_calls(), &2);
+    }
+
+    #[test]
+    fn test_with_prefix() {
+        let break_timer =
+            ThrottleTimer::new(Duration::from_secs(1_u64), "Break").with_prefix("billing: ");
+        assert!(format!("{}", break_timer).starts_with("billing: Break called"));
+        assert_eq!(break_timer.event_name(), "Break");
+        assert_eq!(break_timer.stats().event_name, "Break");
+    }
+
+    #[test]
+    fn test_run_sampled() {
+        let clock = MockClock::new();
+        let mut never_timer =
+            ThrottleTimer::new(Duration::from_secs(1_u64), "Never").with_clock(clock.clone());
+        for _ in 0..100 {
+            assert!(!never_timer.run_sampled(0.0, &mut || {}));
+        }
+        // failed rolls leave the window open
+        assert!(never_timer.can_run());
+        assert!(never_timer.run(&mut || {}));
+
+        let mut always_timer =
+            ThrottleTimer::new(Duration::from_secs(1_u64), "Always").with_clock(clock.clone());
+        assert!(always_timer.run_sampled(1.0, &mut || {}));
+        assert!(!always_timer.run_sampled(1.0, &mut || {}));
+        clock.advance(Duration::from_secs(1_u64));
+        assert!(always_timer.run_sampled(1.0, &mut || {}));
+        assert_eq!(always_timer.total_calls(), &2);
+
+        let mut half_timer =
+            ThrottleTimer::new(Duration::from_secs(0_u64), "Half").with_clock(clock.clone());
+        for _ in 0..1_000 {
+            half_timer.run_sampled(0.5, &mut || {});
+        }
+        assert!(half_timer.total_calls() > &350 && half_timer.total_calls() < &650);
+
+        let mut clamped_timer =
+            ThrottleTimer::new(Duration::from_secs(0_u64), "Clamped").with_clock(clock);
+        for _ in 0..100 {
+            assert!(!clamped_timer.run_sampled(f64::NAN, &mut || {}));
+            assert!(!clamped_timer.run_sampled(-1.0, &mut || {}));
+            assert!(clamped_timer.run_sampled(2.0, &mut || {}));
+        }
+        assert_eq!(clamped_timer.total_calls(), &100);
+    }
+
+    #[test]
+    fn test_reset_to() {
+        let clock = MockClock::new();
+        let mut break_timer = ThrottleTimer::new(Duration::from_secs(60_u64), "Break")
+            .with_history(4)
+            .with_interval_histogram(vec![Duration::from_secs(60_u64)])
+            .with_clock(clock.clone());
+        for _ in 0..3 {
+            assert!(break_timer.run_tallied(&mut || Err(())));
+            clock.advance(Duration::from_secs(60_u64));
+        }
+        break_timer.suspend_until(clock.now() + Duration::from_secs(600_u64));
+
+        let state = ThrottleState {
+            last_called_ago: Some(Duration::from_secs(45_u64)),
+            first_called_ago: Some(Duration::from_secs(285_u64)),
+            total_calls: 5,
+            throttled_calls: 7,
+        };
+        break_timer.reset_to(state.clone());
+
+        assert_eq!(break_timer.state(), state);
+        assert_eq!(break_timer.interval_stats(), None);
+        assert_eq!(break_timer.interval_histogram().unwrap().total(), 0);
+        assert_eq!(break_timer.history().count(), 0);
+        assert_eq!(break_timer.failure_count(), 0);
+        assert!(!break_timer.can_run());
+        assert_eq!(break_timer.wait_time(), Duration::from_secs(15_u64));
+        assert_eq!(break_timer.total_attempts(), 12);
+        assert_eq!(
+            break_timer.average_interval(),
+            Some(Duration::from_secs(60_u64))
+        );
+
+        clock.advance(Duration::from_secs(15_u64));
+        assert!(break_timer.can_run());
+        break_timer.reset_to(ThrottleState {
+            last_called_ago: None,
+            first_called_ago: None,
+            total_calls: 0,
+            throttled_calls: 0,
+        });
+        assert!(break_timer.run(&mut || {}));
+    }
+
+    #[test]
+    fn test_will_fire_within() {
+        let clock = MockClock::new();
+        let mut backup_timer =
+            ThrottleTimer::new(Duration::from_secs(3_600_u64), "Backup").with_clock(clock.clone());
+        assert!(backup_timer.will_fire_within(Duration::from_secs(0_u64)));
+
+        assert!(backup_timer.run(&mut || {}));
+        assert!(!backup_timer.will_fire_within(Duration::from_secs(60_u64)));
+        assert!(backup_timer.will_fire_within(Duration::from_secs(3_600_u64)));
+
+        clock.advance(Duration::from_secs(3_540_u64));
+        assert!(backup_timer.will_fire_within(Duration::from_secs(60_u64)));
+    }
+
+    #[test]
+    fn test_clear_history() {
+        let clock = MockClock::new();
+        let mut break_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Break")
+            .with_clock(clock.clone())
+            .with_history(5);
+        for _ in 0..3 {
+            assert!(break_timer.run(&mut || {}));
+            clock.advance(Duration::from_secs(1_u64));
+        }
+        assert_eq!(break_timer.history().count(), 3);
+
+        break_timer.clear_history();
+        assert_eq!(break_timer.history().count(), 0);
+        assert_eq!(break_timer.total_calls(), &3);
+
+        assert!(break_timer.run(&mut || {}));
+        assert_eq!(break_timer.history().collect::<Vec<_>>(), vec![clock.now()]);
+    }
+
+    #[test]
+    fn test_run_tallied() {
+        let clock = MockClock::new();
+        let mut upload_timer =
+            ThrottleTimer::new(Duration::from_secs(1_u64), "Upload").with_clock(clock.clone());
+        let mut attempt = 0;
+        for _ in 0..5 {
+            assert!(upload_timer.run_tallied(&mut || {
+                attempt += 1;
+                if attempt % 2 == 0 {
+                    Err(())
+                } else {
+                    Ok(())
+                }
+            }));
+            // window consumed whatever the result
+            assert!(!upload_timer.run_tallied(&mut || Ok(())));
+            clock.advance(Duration::from_secs(1_u64));
+        }
+
+        assert_eq!(upload_timer.success_count(), 3);
+        assert_eq!(upload_timer.failure_count(), 2);
+        assert_eq!(upload_timer.total_calls(), &5);
+    }
+
+    #[test]
+    fn test_approx_memory_footprint() {
+        let plain_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Plain");
+        let small_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Small").with_history(10);
+        let large_timer =
+            ThrottleTimer::new(Duration::from_secs(1_u64), "Large").with_history(1_000);
+
+        assert!(plain_timer.approx_memory_footprint() >= std::mem::size_of::<ThrottleTimer>());
+        assert!(small_timer.approx_memory_footprint() > plain_timer.approx_memory_footprint());
+        assert!(
+            large_timer.approx_memory_footprint()
+                >= small_timer.approx_memory_footprint()
+                    + 990 * std::mem::size_of::<std::time::Instant>()
+        );
+    }
+
+    #[test]
+    fn test_fire_or_deadline() {
+        let clock = MockClock::new();
+        let mut flush_timer =
+            ThrottleTimer::new(Duration::from_secs(1_u64), "Flush").with_clock(clock.clone());
+        let mut flushes = 0;
+
+        assert_eq!(flush_timer.fire_or_deadline(&mut || flushes += 1), Ok(()));
+        clock.advance(Duration::from_millis(400_u64));
+        let retry_at = flush_timer.fire_or_deadline(&mut || flushes += 1);
+        assert_eq!(retry_at, Err(clock.now() + Duration::from_millis(600_u64)));
+        assert_eq!(flushes, 1);
+
+        clock.advance(Duration::from_millis(600_u64));
+        assert_eq!(flush_timer.fire_or_deadline(&mut || flushes += 1), Ok(()));
+        assert_eq!(flushes, 2);
+    }
+
+    #[test]
+    fn test_clock_resolution_within_resolution() {
+        let clock = MockClock::new();
+        let resolution = Duration::from_millis(10_u64);
+        let mut hot_timer = ThrottleTimer::new(Duration::from_millis(50_u64), "Hot")
+            .with_clock(clock.clone())
+            .with_clock_resolution(resolution);
+
+        let mut fired_times = Vec::new();
+        for _ in 0..10_000 {
+            let lag = clock.now().duration_since(hot_timer.now());
+            assert!(lag <= resolution, "reading lags by {:?}", lag);
+            if hot_timer.run(&mut || {}) {
+                fired_times.push(clock.now());
+            }
+            clock.advance(Duration::from_millis(1_u64));
+        }
+
+        // stale readings only delay fires, by at most the resolution
+        for gap in fired_times.windows(2).map(|pair| pair[1] - pair[0]) {
+            assert!(gap >= Duration::from_millis(50_u64));
+            assert!(gap <= Duration::from_millis(50_u64) + resolution);
+        }
+        assert!(
+            hot_timer
+                .clock_cache
+                .reads_per_refresh
+                .load(Ordering::Relaxed)
+                > 1
+        );
+    }
+
+    #[test]
+    fn test_clock_resolution_idle_then_burst() {
+        let clock = MockClock::new();
+        let mut hot_timer = ThrottleTimer::new(Duration::from_millis(50_u64), "Hot")
+            .with_clock(clock.clone())
+            .with_clock_resolution(Duration::from_millis(10_u64))
+            .with_min_gap_assertion(true)
+            .with_history(4);
+        assert!(hot_timer.run(&mut || {}));
+        clock.advance(Duration::from_millis(50_u64));
+        for _ in 0..10_000 {
+            assert!(hot_timer.is_ready());
+            clock.advance(Duration::from_nanos(1_u64));
+        }
+        assert_eq!(
+            hot_timer
+                .clock_cache
+                .reads_per_refresh
+                .load(Ordering::Relaxed),
+            super::MAX_READS_PER_REFRESH
+        );
+
+        clock.advance(Duration::from_secs(3_600_u64));
+        let mut fired_attempts = Vec::new();
+        let mut fired_times = Vec::new();
+        for attempt in 0..10_000 {
+            if hot_timer.run(&mut || fired_times.push(clock.now())) {
+                fired_attempts.push(attempt);
+            }
+            clock.advance(Duration::from_micros(1_u64));
+        }
+        // the burst is shorter than the interval so a second fire would be early
+        assert_eq!(fired_attempts.len(), 1);
+        assert!(fired_attempts[0] <= super::MAX_READS_PER_REFRESH as usize);
+        // the fire is recorded at the real time, not the stale reading
+        assert_eq!(hot_timer.history().last(), fired_times.last().copied());
+    }
+
+    #[test]
+    fn test_clock_resolution_burst_then_slow() {
+        let clock = MockClock::new();
+        let resolution = Duration::from_millis(10_u64);
+        let mut hot_timer = ThrottleTimer::new(Duration::from_millis(50_u64), "Hot")
+            .with_clock(clock.clone())
+            .with_clock_resolution(resolution)
+            .with_min_gap_assertion(true);
+        for _ in 0..200_000 {
+            let lag = clock.now().duration_since(hot_timer.now());
+            assert!(lag <= resolution, "reading lags by {:?}", lag);
+            hot_timer.run(&mut || {});
+            clock.advance(Duration::from_micros(1_u64));
+        }
+
+        // every slow attempt is due, only readings handed out during the burst can throttle them
+        let mut throttled_attempts = 0;
+        for _ in 0..100 {
+            clock.advance(Duration::from_secs(1_u64));
+            if !hot_timer.run(&mut || {}) {
+                throttled_attempts += 1;
+            }
+        }
+        assert!(throttled_attempts < super::MAX_READS_PER_REFRESH as usize);
+        assert_eq!(
+            hot_timer
+                .clock_cache
+                .reads_per_refresh
+                .load(Ordering::Relaxed),
+            1
+        );
+    }
+
+    /// `MockClock` counting reads of the monotonic time
+    #[derive(Debug)]
+    struct CountingClock {
+        clock: MockClock,
+        reads: Arc<AtomicUsize>,
+    }
+
+    impl Clock for CountingClock {
+        fn now(&self) -> std::time::Instant {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            self.clock.now()
+        }
+        fn system_now(&self) -> std::time::SystemTime {
+            self.clock.system_now()
+        }
+    }
+
+    #[test]
+    fn test_clock_resolution_clock_reads() {
+        let clock_reads = |maybe_resolution: Option<Duration>| {
+            let clock = MockClock::new();
+            let reads = Arc::new(AtomicUsize::new(0));
+            let mut hot_timer =
+                ThrottleTimer::new(Duration::from_millis(1_u64), "Hot").with_clock(CountingClock {
+                    clock: clock.clone(),
+                    reads: Arc::clone(&reads),
+                });
+            if let Some(resolution) = maybe_resolution {
+                hot_timer = hot_timer.with_clock_resolution(resolution);
+            }
+            for _ in 0..10_000 {
+                hot_timer.run(&mut || {});
+                clock.advance(Duration::from_micros(1_u64));
+            }
+            assert!(*hot_timer.total_calls() >= 9);
+            reads.load(Ordering::SeqCst)
+        };
+
+        assert!(clock_reads(None) >= 10_000);
+        let coarse_reads = clock_reads(Some(Duration::from_micros(100_u64)));
+        assert!(coarse_reads < 2_000, "{} clock reads", coarse_reads);
+    }
+
+    #[test]
+    fn test_run_ready() {
+        let clock = MockClock::new();
+        let mut timers = [
+            ThrottleTimer::new(Duration::from_secs(1_u64), "Heartbeat").with_clock(clock.clone()),
+            ThrottleTimer::new(Duration::from_secs(3_u64), "Report").with_clock(clock.clone()),
+        ];
+        let fired = std::cell::RefCell::new(Vec::new());
+
+        for _ in 0..4 {
+            run_ready(&mut timers, |index| fired.borrow_mut().push(index));
+            clock.advance(Duration::from_secs(1_u64));
+        }
+
+        assert_eq!(fired.into_inner(), vec![0, 1, 0, 0, 0, 1]);
+        assert_eq!(timers[0].total_calls(), &4);
+        assert_eq!(timers[1].total_calls(), &2);
+    }
+
+    #[test]
+    fn test_permits_in() {
+        let clock = MockClock::new();
+        let mut break_timer =
+            ThrottleTimer::new(Duration::from_secs(10_u64), "Break").with_clock(clock.clone());
+        assert_eq!(break_timer.permits_in(Duration::from_secs(0_u64)), 1);
+        assert_eq!(break_timer.permits_in(Duration::from_secs(25_u64)), 3);
+
+        assert!(break_timer.run(&mut || {}));
+        clock.advance(Duration::from_secs(4_u64));
+        assert_eq!(break_timer.permits_in(Duration::from_secs(5_u64)), 0);
+        assert_eq!(break_timer.permits_in(Duration::from_secs(16_u64)), 2);
+    }
+
+    #[test]
+    fn test_next_fire_times() {
+        let clock = MockClock::new();
+        let mut break_timer =
+            ThrottleTimer::new(Duration::from_secs(10_u64), "Break").with_clock(clock.clone());
+        assert!(break_timer.run(&mut || {}));
+        let now = clock.now();
+
+        assert_eq!(
+            break_timer.next_fire_times(3),
+            vec![
+                now + Duration::from_secs(10_u64),
+                now + Duration::from_secs(20_u64),
+                now + Duration::from_secs(30_u64),
+            ]
+        );
+        assert!(break_timer.next_fire_times(0).is_empty());
+
+        // would overflow Instant
+        break_timer.set_max_frequency(Duration::from_secs(u64::MAX));
+        break_timer.open_window();
+        assert_eq!(break_timer.next_fire_times(3), vec![now]);
+    }
+
+    #[test]
+    fn test_zero_frequency_capacity() {
+        let clock = MockClock::new();
+        let mut always_timer =
+            ThrottleTimer::new(Duration::from_secs(0_u64), "Always").with_clock(clock.clone());
+        assert!(always_timer.run(&mut || {}));
+
+        assert_eq!(
+            always_timer.permits_in(Duration::from_secs(1_u64)),
+            usize::MAX
+        );
+        assert_eq!(
+            always_timer.permits_in(Duration::from_secs(0_u64)),
+            usize::MAX
+        );
+        assert_eq!(always_timer.next_fire_times(3), vec![clock.now()]);
+        assert_eq!(always_timer.next_fire_times(usize::MAX), vec![clock.now()]);
+        assert!(always_timer.next_fire_times(0).is_empty());
+
+        always_timer.suspend_until(clock.now() + Duration::from_secs(3_600_u64));
+        assert_eq!(always_timer.permits_in(Duration::from_secs(1_u64)), 0);
+        assert!(!always_timer.will_fire_within(Duration::from_secs(1_u64)));
+        assert_eq!(
+            always_timer.permits_in(Duration::from_secs(3_600_u64)),
+            usize::MAX
+        );
+    }
+
+    #[test]
+    fn test_effective_rate_limited() {
+        let clock = MockClock::new();
+        let mut alert_timer =
+            ThrottleTimer::new(Duration::from_secs(60_u64), "Alert").with_clock(clock.clone());
+        assert!(alert_timer.run(&mut || {}));
+        assert!(!alert_timer.effective_rate_limited());
+
+        assert!(!alert_timer.run(&mut || {}));
+        assert!(alert_timer.effective_rate_limited());
+
+        clock.advance(Duration::from_secs(60_u64));
+        assert!(!alert_timer.effective_rate_limited());
+    }
+
+    #[test]
+    fn test_scale_time() {
+        let mut break_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Break");
+        break_timer.scale_time(1_000.0);
+        assert!(break_timer.run(&mut || {}));
+        assert!(!break_timer.run(&mut || {}));
+
+        let mut fires = 0;
+        for _ in 0..5 {
+            thread::sleep(Duration::from_millis(2_u64));
+            if break_timer.run(&mut || {}) {
+                fires += 1;
+            }
+        }
+        assert_eq!(fires, 5);
+    }
+
+    #[test]
+    fn test_idle_reset() {
+        let clock = MockClock::new();
+        let mut poll_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Poll")
+            .with_idle_reset(10)
+            .with_clock(clock.clone());
+        for _ in 0..3 {
+            assert!(poll_timer.run(&mut || {}));
+            clock.advance(Duration::from_secs(2_u64));
+        }
+        assert_eq!(poll_timer.interval_stats().unwrap().samples, 2);
+
+        clock.advance(Duration::from_secs(60_u64));
+        assert!(poll_timer.run(&mut || {}));
+        assert_eq!(poll_timer.interval_stats(), None);
+        assert_eq!(poll_timer.average_interval(), None);
+        assert_eq!(poll_timer.total_calls(), &4);
+
+        clock.advance(Duration::from_secs(3_u64));
+        assert!(poll_timer.run(&mut || {}));
+        assert_eq!(
+            poll_timer.interval_stats(),
+            Some(IntervalStats {
+                min: Duration::from_secs(3_u64),
+                avg: Duration::from_secs(3_u64),
+                max: Duration::from_secs(3_u64),
+                samples: 1,
+            })
+        );
+        assert_eq!(
+            poll_timer.average_interval(),
+            Some(Duration::from_secs(3_u64))
+        );
+    }
+
+    #[test]
+    fn test_remaining_budget() {
+        let clock = MockClock::new();
+        let mut cpu_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Cpu")
+            .with_clock(clock.clone())
+            .with_time_budget(Duration::from_millis(100_u64));
+        assert_eq!(cpu_timer.remaining_budget(), Duration::from_millis(100_u64));
+
+        assert!(cpu_timer.run_costed(Duration::from_millis(40_u64), &mut || {}));
+        clock.advance(Duration::from_millis(600_u64));
+        assert!(cpu_timer.run_costed(Duration::from_millis(35_u64), &mut || {}));
+        assert_eq!(cpu_timer.remaining_budget(), Duration::from_millis(25_u64));
+
+        // first cost leaves the window
+        clock.advance(Duration::from_millis(400_u64));
+        assert_eq!(cpu_timer.remaining_budget(), Duration::from_millis(65_u64));
+
+        let break_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Break");
+        assert_eq!(break_timer.remaining_budget(), Duration::MAX);
+    }
+
+    #[test]
+    fn test_set_event_name() {
+        let clock = MockClock::new();
+        let mut alert_timer = ThrottleTimer::new(Duration::from_secs(60_u64), "Alert")
+            .with_history(4)
+            .with_clock(clock.clone());
+        assert!(alert_timer.run(&mut || {}));
+        assert!(!alert_timer.run(&mut || {}));
+        let stats = alert_timer.stats();
+
+        alert_timer.set_event_name("Page");
+        assert_eq!(alert_timer.event_name(), "Page");
+        assert_eq!(
+            alert_timer.stats(),
+            Stats {
+                event_name: "Page",
+                ..stats
+            }
+        );
+        assert_eq!(alert_timer.history().count(), 1);
+        assert!(!alert_timer.can_run());
+    }
+
+    #[test]
+    fn test_interval_histogram() {
+        let clock = MockClock::new();
+        let mut poll_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Poll")
+            .with_interval_histogram(vec![
+                Duration::from_secs(2_u64),
+                Duration::from_secs(10_u64),
+            ])
+            .with_clock(clock.clone());
+        for secs in &[0_u64, 1, 2, 5, 30] {
+            clock.advance(Duration::from_secs(*secs));
+            poll_timer.run(&mut || {});
+        }
+        assert_eq!(
+            poll_timer.interval_histogram().unwrap().counts(),
+            &[2, 1, 1]
+        );
+        assert_eq!(poll_timer.interval_histogram().unwrap().total(), 4);
+    }
+
+    #[test]
+    fn test_no_panic_on_overflow() {
+        let clock = MockClock::new();
+        let mut never_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Never")
+            .with_startup_delay(Duration::MAX)
+            .with_clock(clock.clone());
+        assert!(!never_timer.run(&mut || {}));
+        assert_eq!(never_timer.wait_time(), Duration::MAX);
+        assert_eq!(never_timer.try_ready_at(), Err(Error::TimeOverflow));
+        assert_eq!(
+            never_timer.try_fire_or_deadline(&mut || {}),
+            Err(Error::TimeOverflow)
+        );
+        assert!(never_timer.next_fire_times(3).is_empty());
+        assert_eq!(never_timer.permits_in(Duration::from_secs(60_u64)), 0);
+
+        // counters saturate instead of overflowing
+        let mut busy_timer = ThrottleTimer::resuming(
+            Duration::from_secs(0_u64),
+            "Busy",
+            std::time::Instant::now(),
+            usize::MAX,
+        );
+        assert!(busy_timer.run(&mut || {}));
+        assert_eq!(busy_timer.total_calls(), &usize::MAX);
+        let other_busy_timer = ThrottleTimer::resuming(
+            Duration::from_secs(0_u64),
+            "Busy",
+            std::time::Instant::now(),
+            usize::MAX,
+        );
+        busy_timer.absorb(&other_busy_timer);
+        assert_eq!(busy_timer.total_calls(), &usize::MAX);
+
+        let mut alert_timer =
+            ThrottleTimer::new(Duration::from_secs(60_u64), "Alert").with_clock(clock.clone());
+        alert_timer.reset_to(ThrottleState {
+            last_called_ago: Some(Duration::from_secs(0_u64)),
+            first_called_ago: None,
+            total_calls: usize::MAX,
+            throttled_calls: usize::MAX,
+        });
+        assert!(!alert_timer.run(&mut || {}));
+        assert_eq!(alert_timer.throttled_calls(), &usize::MAX);
+        assert_eq!(alert_timer.total_attempts(), usize::MAX);
+
+        let mut cpu_timer = ThrottleTimer::new(Duration::MAX, "Cpu")
+            .with_clock(clock.clone())
+            .with_time_budget(Duration::from_millis(100_u64));
+        assert!(cpu_timer.run_costed(Duration::from_millis(10_u64), &mut || {}));
+        assert!(!cpu_timer.run_costed(Duration::MAX, &mut || {}));
+        assert_eq!(cpu_timer.try_ready_at(), Err(Error::TimeOverflow));
+
+        let now = clock.now();
+        assert_eq!(clock.try_advance(Duration::MAX), Err(Error::TimeOverflow));
+        clock.sleep(Duration::MAX);
+        assert_eq!(clock.now(), now);
+
+        // unscaling the sleep overflows Duration
+        let mut slow_timer =
+            ThrottleTimer::new(Duration::from_secs(1_u64), "Slow").with_clock(clock.clone());
+        slow_timer.scale_time(f64::MIN_POSITIVE);
+        assert!(slow_timer.run(&mut || {}));
+        slow_timer.run_wait(&mut || {});
+        assert_eq!(slow_timer.total_calls(), &1);
+        assert_eq!(slow_timer.throttled_calls(), &1);
+
+        // the history is not allocated up front
+        let mut log_timer = ThrottleTimer::new(Duration::from_secs(0_u64), "Log")
+            .with_clock(clock.clone())
+            .with_history(usize::MAX);
+        assert!(log_timer.run(&mut || {}));
+        assert!(log_timer.run(&mut || {}));
+        assert_eq!(log_timer.history().count(), 2);
+    }
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tracing_test {
+    use super::ThrottleTimer;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+    use std::time::Duration;
+    use tracing::{span, Event, Metadata, Subscriber};
+
+    /// Counts events that carry an `event_name` field
+    struct CountingSubscriber(Arc<AtomicUsize>);
+
+    impl Subscriber for CountingSubscriber {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+        fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+            span::Id::from_u64(1)
+        }
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+        fn event(&self, event: &Event<'_>) {
+            if event.fields().any(|field| field.name() == "event_name") {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+        fn enter(&self, _: &span::Id) {}
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    #[test]
+    fn test_tracing_event_on_fire() {
+        let events = Arc::new(AtomicUsize::new(0));
+        let subscriber = CountingSubscriber(Arc::clone(&events));
+        tracing::subscriber::with_default(subscriber, || {
+            let mut break_timer = ThrottleTimer::new(Duration::from_secs(60_u64), "Break");
+            assert!(break_timer.run(&mut || {}));
+            assert!(!break_timer.run(&mut || {}));
+            assert!(!break_timer.run(&mut || {}));
+        });
+        assert_eq!(events.load(Ordering::SeqCst), 1);
+    }
 }
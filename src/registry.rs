@@ -0,0 +1,193 @@
+use crate::clock::{Clock, SystemClock};
+use crate::ThrottleTimer;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Named collection of timers sharing one clock.
+///
+/// Injecting a `MockClock` with `with_clock` lets a test advance every registered timer together.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use throttle_timer::ThrottleRegistry;
+///
+/// let mut registry = ThrottleRegistry::new();
+/// registry.register("Email", Duration::from_secs(60_u64));
+/// registry.register("Sms", Duration::from_secs(600_u64));
+///
+/// if let Some(email_timer) = registry.get_mut("Email") {
+///     assert!(email_timer.run(&mut || {}));
+/// }
+/// assert_eq!(registry.len(), 2);
+/// ```
+#[derive(Debug)]
+pub struct ThrottleRegistry {
+    timers: HashMap<&'static str, ThrottleTimer>,
+    clock: Arc<dyn Clock>,
+}
+
+impl ThrottleRegistry {
+    pub fn new() -> Self {
+        Self {
+            timers: HashMap::new(),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Reads time from `clock` instead of the system clock, for every timer registered afterwards
+    pub fn with_clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Creates a timer using the registry's clock, replacing any timer registered with the same name
+    pub fn register(
+        &mut self,
+        event_name: &'static str,
+        max_frequency: Duration,
+    ) -> &mut ThrottleTimer {
+        let timer =
+            ThrottleTimer::new(max_frequency, event_name).with_clock(Arc::clone(&self.clock));
+        match self.timers.entry(event_name) {
+            Entry::Occupied(mut entry) => {
+                entry.insert(timer);
+                entry.into_mut()
+            }
+            Entry::Vacant(entry) => entry.insert(timer),
+        }
+    }
+    /// Moves every timer from `other` into this registry.
+    ///
+    /// When both registries have a timer with the same name, `other`'s timer is combined into the existing one
+    /// with `ThrottleTimer::absorb`, so counts are summed and the later last call time wins.
+    /// Timers moved across keep reading from the clock of the registry they were registered in
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::ThrottleRegistry;
+    ///
+    /// let mut registry = ThrottleRegistry::new();
+    /// registry.register("Email", Duration::from_secs(60_u64));
+    /// let mut billing = ThrottleRegistry::new();
+    /// billing.register("Invoice", Duration::from_secs(60_u64));
+    ///
+    /// registry.extend(billing);
+    /// assert_eq!(registry.len(), 2);
+    /// ```
+    pub fn extend(&mut self, other: ThrottleRegistry) {
+        for (event_name, timer) in other.timers {
+            match self.timers.entry(event_name) {
+                Entry::Occupied(mut entry) => entry.get_mut().absorb(&timer),
+                Entry::Vacant(entry) => {
+                    entry.insert(timer);
+                }
+            }
+        }
+    }
+    pub fn get(&self, event_name: &str) -> Option<&ThrottleTimer> {
+        self.timers.get(event_name)
+    }
+    pub fn get_mut(&mut self, event_name: &str) -> Option<&mut ThrottleTimer> {
+        self.timers.get_mut(event_name)
+    }
+    pub fn len(&self) -> usize {
+        self.timers.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.timers.is_empty()
+    }
+    /// Registered timers in arbitrary order
+    pub fn iter(&self) -> impl Iterator<Item = &ThrottleTimer> {
+        self.timers.values()
+    }
+}
+
+impl Default for ThrottleRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ThrottleRegistry;
+    use crate::{MockClock, ThrottleTimer};
+    use std::time::Duration;
+
+    #[test]
+    fn test_shared_clock() {
+        let clock = MockClock::new();
+        let mut registry = ThrottleRegistry::new().with_clock(clock.clone());
+        assert!(registry
+            .register("Email", Duration::from_secs(60_u64))
+            .run(&mut || {}));
+        assert!(registry
+            .register("Sms", Duration::from_secs(30_u64))
+            .run(&mut || {}));
+
+        clock.advance(Duration::from_secs(30_u64));
+        assert!(registry.get_mut("Sms").unwrap().can_run());
+        assert!(!registry.get_mut("Email").unwrap().can_run());
+
+        clock.advance(Duration::from_secs(30_u64));
+        assert!(registry.get_mut("Email").unwrap().can_run());
+        assert!(registry.get_mut("Sms").unwrap().can_run());
+        assert!(registry
+            .iter()
+            .all(|timer| timer.wait_time() == Duration::from_secs(0_u64)));
+    }
+
+    #[test]
+    fn test_extend() {
+        let clock = MockClock::new();
+        let mut alerts = ThrottleRegistry::new().with_clock(clock.clone());
+        let email_timer = alerts.register("Email", Duration::from_secs(1_u64));
+        email_timer.run_tallied(&mut || Ok(()));
+        email_timer.run(&mut || {});
+        alerts.register("Sms", Duration::from_secs(1_u64));
+
+        let mut billing = ThrottleRegistry::new().with_clock(clock.clone());
+        billing
+            .register("Email", Duration::from_secs(1_u64))
+            .run_tallied(&mut || Err(()));
+        billing
+            .register("Invoice", Duration::from_secs(1_u64))
+            .run(&mut || {});
+
+        alerts.extend(billing);
+        assert_eq!(alerts.len(), 3);
+        let email_timer = alerts.get("Email").unwrap();
+        assert_eq!(email_timer.total_calls(), &2);
+        assert_eq!(email_timer.throttled_calls(), &1);
+        assert_eq!(email_timer.success_count(), 1);
+        assert_eq!(email_timer.failure_count(), 1);
+        assert_eq!(alerts.get("Invoice").unwrap().total_calls(), &1);
+        assert_eq!(alerts.get("Sms").unwrap().total_calls(), &0);
+
+        // interval histograms with the same bounds are merged
+        let bounds = vec![Duration::from_secs(2_u64)];
+        let mut alerts = ThrottleRegistry::new().with_clock(clock.clone());
+        let mut billing = ThrottleRegistry::new().with_clock(clock.clone());
+        for registry in [&mut alerts, &mut billing] {
+            let email_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Email")
+                .with_clock(clock.clone())
+                .with_interval_histogram(bounds.clone());
+            registry.timers.insert("Email", email_timer);
+        }
+
+        for secs in &[0_u64, 1, 5] {
+            clock.advance(Duration::from_secs(*secs));
+            alerts.get_mut("Email").unwrap().run(&mut || {});
+            billing.get_mut("Email").unwrap().run(&mut || {});
+        }
+
+        alerts.extend(billing);
+        let email_timer = alerts.get("Email").unwrap();
+        assert_eq!(email_timer.interval_histogram().unwrap().counts(), &[2, 2]);
+        assert_eq!(email_timer.interval_stats().unwrap().samples, 4);
+    }
+}
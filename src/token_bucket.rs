@@ -0,0 +1,166 @@
+use crate::clock::{Clock, SystemClock};
+use std::convert::TryFrom;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Allows bursts of up to `capacity` runs, refilling one token every `refill_every`.
+///
+/// The bucket starts full. A zero `refill_every` keeps the bucket full.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use throttle_timer::TokenBucket;
+///
+/// let mut upload_limit = TokenBucket::new(10, Duration::from_secs(1_u64));
+/// assert!(upload_limit.run_weighted(7, &mut || {}));
+/// assert!(!upload_limit.run_weighted(7, &mut || {}));
+/// assert!(upload_limit.run(&mut || {}));
+/// assert_eq!(upload_limit.available(), 2);
+/// ```
+#[derive(Debug)]
+pub struct TokenBucket {
+    capacity: u32,
+    refill_every: Duration,
+    tokens: u32,
+    last_refill_time: Instant,
+    clock: Arc<dyn Clock>,
+}
+
+impl TokenBucket {
+    pub fn new(capacity: u32, refill_every: Duration) -> Self {
+        Self {
+            capacity,
+            refill_every,
+            tokens: capacity,
+            last_refill_time: Instant::now(),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Reads time from `clock` instead of the system clock
+    pub fn with_clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+        self.last_refill_time = clock.now();
+        self.clock = Arc::new(clock);
+        self
+    }
+    pub const fn capacity(&self) -> u32 {
+        self.capacity
+    }
+    pub const fn refill_every(&self) -> &Duration {
+        &self.refill_every
+    }
+
+    /// Tokens that can be consumed right now
+    pub fn available(&self) -> u32 {
+        self.refilled_at(self.clock.now()).0
+    }
+
+    /// Runs `success` consuming one token
+    pub fn run(&mut self, success: &mut dyn FnMut()) -> bool {
+        self.run_weighted(1, success)
+    }
+
+    /// Runs `success` if `weight` tokens are available, consuming all of them.
+    /// A weight larger than `capacity` never runs
+    pub fn run_weighted(&mut self, weight: u32, success: &mut dyn FnMut()) -> bool {
+        self.refill();
+        let run_flag = self.tokens >= weight;
+        if run_flag {
+            self.tokens -= weight;
+            success();
+        }
+        run_flag
+    }
+
+    /// Consumes `n` tokens if all of them are available, otherwise consumes none
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use throttle_timer::TokenBucket;
+    ///
+    /// let mut batch_limit = TokenBucket::new(5, Duration::from_secs(1_u64));
+    /// assert!(batch_limit.try_acquire_n(3));
+    /// assert!(!batch_limit.try_acquire_n(3));
+    /// ```
+    pub fn try_acquire_n(&mut self, n: usize) -> bool {
+        self.refill();
+        match u32::try_from(n) {
+            Ok(n) if n <= self.tokens => {
+                self.tokens -= n;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn refill(&mut self) {
+        let (tokens, last_refill_time) = self.refilled_at(self.clock.now());
+        self.tokens = tokens;
+        self.last_refill_time = last_refill_time;
+    }
+
+    /// Token count and refill time after adding the tokens earned up to `now`
+    fn refilled_at(&self, now: Instant) -> (u32, Instant) {
+        let refill_nanos = self.refill_every.as_nanos();
+        if self.tokens >= self.capacity || refill_nanos == 0 {
+            return (self.capacity, now);
+        }
+        let earned = now.duration_since(self.last_refill_time).as_nanos() / refill_nanos;
+        let missing = u128::from(self.capacity - self.tokens);
+        if earned >= missing {
+            return (self.capacity, now);
+        }
+        // earned is less than capacity so fits in u32
+        let earned = earned as u32;
+        (
+            self.tokens + earned,
+            self.last_refill_time + self.refill_every * earned,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::TokenBucket;
+    use crate::MockClock;
+    use std::time::Duration;
+
+    #[test]
+    fn test_run_weighted() {
+        let clock = MockClock::new();
+        let mut upload_limit =
+            TokenBucket::new(10, Duration::from_secs(1_u64)).with_clock(clock.clone());
+
+        assert!(upload_limit.run_weighted(7, &mut || {}));
+        assert!(!upload_limit.run_weighted(7, &mut || {}));
+        assert_eq!(upload_limit.available(), 3);
+
+        clock.advance(Duration::from_millis(3_500_u64));
+        assert_eq!(upload_limit.available(), 6);
+        assert!(!upload_limit.run_weighted(7, &mut || {}));
+
+        clock.advance(Duration::from_millis(500_u64));
+        assert!(upload_limit.run_weighted(7, &mut || {}));
+        assert_eq!(upload_limit.available(), 0);
+        assert!(!upload_limit.run_weighted(11, &mut || {}));
+    }
+
+    #[test]
+    fn test_try_acquire_n() {
+        let clock = MockClock::new();
+        let mut batch_limit =
+            TokenBucket::new(5, Duration::from_secs(1_u64)).with_clock(clock.clone());
+
+        assert!(batch_limit.try_acquire_n(3));
+        assert!(!batch_limit.try_acquire_n(3));
+        // a failed acquire consumes nothing
+        assert_eq!(batch_limit.available(), 2);
+
+        clock.advance(Duration::from_secs(1_u64));
+        assert!(batch_limit.try_acquire_n(3));
+        assert!(!batch_limit.try_acquire_n(usize::MAX));
+        assert_eq!(batch_limit.available(), 0);
+    }
+}
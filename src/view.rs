@@ -0,0 +1,62 @@
+use crate::{Stats, ThrottleTimer};
+use std::time::Duration;
+
+/// Read only view of a `ThrottleTimer`, see `ThrottleTimer::downgrade_to_readonly`.
+///
+/// Only exposes methods that can not consume the window, so observer code can not fire the timer
+///
+/// ```compile_fail
+/// use std::time::Duration;
+/// use throttle_timer::ThrottleTimer;
+///
+/// let break_timer = ThrottleTimer::new(Duration::from_secs(1_u64), "Break");
+/// let view = break_timer.downgrade_to_readonly();
+/// view.run(&mut || {});
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct ReadOnlyThrottleView<'a> {
+    timer: &'a ThrottleTimer,
+}
+
+impl<'a> ReadOnlyThrottleView<'a> {
+    pub(crate) const fn new(timer: &'a ThrottleTimer) -> Self {
+        Self { timer }
+    }
+    pub fn event_name(&self) -> &str {
+        self.timer.event_name()
+    }
+    pub fn is_ready(&self) -> bool {
+        self.timer.is_ready()
+    }
+    pub fn wait_time(&self) -> Duration {
+        self.timer.wait_time()
+    }
+    pub fn stats(&self) -> Stats {
+        self.timer.stats()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{MockClock, ThrottleTimer};
+    use std::time::Duration;
+
+    #[test]
+    fn test_read_only_view() {
+        let clock = MockClock::new();
+        let mut break_timer =
+            ThrottleTimer::new(Duration::from_secs(1_u64), "Break").with_clock(clock.clone());
+        assert!(break_timer.run(&mut || {}));
+
+        let view = break_timer.downgrade_to_readonly();
+        assert_eq!(view.event_name(), "Break");
+        assert!(!view.is_ready());
+        assert_eq!(view.wait_time(), Duration::from_secs(1_u64));
+        assert_eq!(view.stats().total_calls, 1);
+
+        clock.advance(Duration::from_secs(1_u64));
+        assert!(view.is_ready());
+        // the view did not consume the window
+        assert!(break_timer.run(&mut || {}));
+    }
+}